@@ -0,0 +1,66 @@
+//! Times the Intcode interpreter on the days which lean on it hardest.
+//!
+//! There's no benchmarking harness here, so each solver is simply run a few times and the mean
+//! and fastest times reported.  Run with `cargo bench`, from the `d2019` directory so that the
+//! inputs can be found.
+
+use std::{
+	fs,
+	hint::black_box,
+	time::{Duration, Instant},
+};
+
+const RUNS: u32 = 10;
+
+fn bench<T, O>(name: &str, day: u8, parse: fn(&str) -> anyhow::Result<T>, solve: fn(&T) -> O) {
+	let Ok(input) = fs::read_to_string(format!("inputs/day{day:02}")) else {
+		println!("{name:<24} skipped, no input for day {day}");
+		return;
+	};
+
+	let parsed = parse(&input).expect("failed to parse input");
+	let mut fastest = Duration::MAX;
+	let mut total = Duration::ZERO;
+
+	for _ in 0..RUNS {
+		let start = Instant::now();
+		black_box(solve(black_box(&parsed)));
+		let elapsed = start.elapsed();
+
+		fastest = fastest.min(elapsed);
+		total += elapsed;
+	}
+
+	println!(
+		"{name:<24} mean {:>10.3?}  fastest {:>10.3?}",
+		total / RUNS,
+		fastest
+	);
+}
+
+fn main() {
+	bench(
+		"day 2, noun/verb search",
+		2,
+		d2019::day02::parse,
+		d2019::day02::part_two,
+	);
+	bench(
+		"day 7, feedback loop",
+		7,
+		d2019::day07::parse,
+		d2019::day07::part_two,
+	);
+	bench(
+		"day 9, BOOST sensor",
+		9,
+		d2019::day09::parse,
+		d2019::day09::part_two,
+	);
+	bench(
+		"day 23, NAT",
+		23,
+		d2019::day23::parse,
+		d2019::day23::part_two,
+	);
+}
@@ -1,60 +1,42 @@
-use crate::intcode;
+use crate::intcode::{Intcode, Program};
 
-pub type Intermediate = Vec<i32>;
-pub type Output = i32;
+pub type Intermediate = Program;
+pub type Output = i64;
 
 /// # Errors
 pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
-	let program: Vec<i32> = input
-		.lines()
-		.flat_map(|line| -> Vec<i32> { line.split(',').map(|n| n.parse::<i32>().unwrap()).collect() })
-		.collect();
-
-	Ok(program)
+	Ok(input.parse()?)
 }
 
 #[must_use]
 pub fn part_one(program: &Intermediate) -> Option<Output> {
-	let program = {
-		let mut program = program.clone();
-		program[1] = 12;
-		program[2] = 2;
-		program
-	};
-
-	let mut intcode: intcode::Intcode = intcode::Intcode::from(program);
-	intcode = intcode.run();
-	let final_memory = intcode.data();
-
-	Some(final_memory[0])
+	let mut intcode = Intcode::from(program.with_noun_verb(12, 2));
+	intcode.run_to_halt().ok()?;
+
+	Some(intcode.read(0))
 }
 
 #[must_use]
 pub fn part_two(program: &Intermediate) -> Option<Output> {
-	let mut done = false;
-	let mut pair = (0, 0);
+	// Clones share memory, and with it the instructions already decoded by earlier attempts.
+	let machine = Intcode::from(program.clone());
 
 	for noun in 0..=100 {
 		for verb in 0..=100 {
-			let program = {
-				let mut program = program.clone();
-				program[1] = noun;
-				program[2] = verb;
-				program
-			};
-
-			let output = intcode::Intcode::from(program).run().data()[0];
-
-			if output == 19_690_720 {
-				done = true;
-				pair = (noun, verb);
-				break;
+			let mut intcode = machine.clone();
+			intcode.write(1, noun).ok()?;
+			intcode.write(2, verb).ok()?;
+
+			// A noun and verb which make the program fault just aren't the answer.
+			if intcode.run_to_halt().is_err() {
+				continue;
+			}
+
+			if intcode.read(0) == 19_690_720 {
+				return Some(noun * 100 + verb);
 			}
-		}
-		if done {
-			break;
 		}
 	}
 
-	Some(pair.0 * 100 + pair.1)
+	None
 }
@@ -1,46 +1,27 @@
-use crate::intcode;
+use crate::intcode::{Intcode, Program};
 
-pub type Intermediate = Vec<i32>;
-pub type Output = i32;
+pub type Intermediate = Program;
+pub type Output = i64;
 
 /// # Errors
 pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
-	let program: Vec<i32> = input
-		.lines()
-		.flat_map(|line| -> Vec<i32> { line.split(',').map(|n| n.parse::<i32>().unwrap()).collect() })
-		.collect();
-
-	Ok(program)
+	Ok(input.parse()?)
 }
 
 #[must_use]
 pub fn part_one(program: &Intermediate) -> Option<Output> {
-	let program = program.clone();
-	let mut intcode: intcode::Intcode = intcode::Intcode::from(program);
+	let mut intcode = Intcode::from(program);
 	intcode.input(1);
-	intcode = intcode.run();
-
-	let mut outputs: Vec<i32> = Vec::new();
-
-	while let Some(output) = intcode.output() {
-		outputs.push(output);
-	}
+	let outputs = intcode.run_to_halt().ok()?;
 
-	Some(*outputs.last().expect("expected an output"))
+	outputs.last().copied()
 }
 
 #[must_use]
 pub fn part_two(program: &Intermediate) -> Option<Output> {
-	let program = program.clone();
-	let mut intcode: intcode::Intcode = intcode::Intcode::from(program);
+	let mut intcode = Intcode::from(program);
 	intcode.input(5);
-	intcode = intcode.run();
-
-	let mut outputs: Vec<i32> = Vec::new();
-
-	while let Some(output) = intcode.output() {
-		outputs.push(output);
-	}
+	let outputs = intcode.run_to_halt().ok()?;
 
-	Some(*outputs.last().expect("expected an output"))
+	outputs.last().copied()
 }
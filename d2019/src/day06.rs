@@ -1,17 +1,49 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use daocutil::tree::Tree;
+
+pub type Intermediate = Tree<String>;
+pub type Output = usize;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	let orbits =
+		daocutil::parse::lines(input, |line| daocutil::scan!(line, "{}){}", String, String))?;
+
+	Ok(Tree::from_edges(orbits)?)
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(orbits: &Intermediate) -> Option<Output> {
+	// Each object directly or indirectly orbits every one of its ancestors.
+	Some(orbits.total_depth())
 }
 
+daocutil::test_example!(
+	part_one_example,
+	parse,
+	part_one,
+	include_str!("examples/day06-1"),
+	Some(42)
+);
+
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(orbits: &Intermediate) -> Option<Output> {
+	let you = orbits.parent(&"YOU".to_string())?;
+	let santa = orbits.parent(&"SAN".to_string())?;
+
+	orbits.distance(you, santa)
+}
+
+daocutil::test_example!(
+	part_two_example,
+	parse,
+	part_two,
+	include_str!("examples/day06-2"),
+	Some(4)
+);
+
+#[test]
+fn malformed_orbits() {
+	assert!(parse("COM)A\nCOM)B\nA)C\nB)C").is_err());
+	assert!(parse("A)B\nB)A").is_err());
+	assert!(parse("COM-A").is_err());
 }
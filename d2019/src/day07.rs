@@ -1,163 +1,61 @@
 use itertools::Itertools;
 
-use crate::intcode::Intcode;
+use crate::intcode::{
+	network::{Chain, Network, Outcome},
+	Intcode, Program,
+};
 
-pub type Intermediate = Vec<i32>;
-pub type Output = i32;
+pub type Intermediate = Program;
+pub type Output = i64;
 
 /// # Errors
 pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
-	let program: Vec<i32> = input
-		.lines()
-		.flat_map(|line| -> Vec<i32> { line.split(',').map(|n| n.parse::<i32>().unwrap()).collect() })
-		.collect();
-
-	Ok(program)
+	Ok(input.parse()?)
 }
 
-#[allow(clippy::many_single_char_names)]
-#[must_use]
-pub fn part_one(program: &Intermediate) -> Option<Output> {
-	let permutations = (0..5).permutations(5);
-
-	let mut max: Option<i32> = None;
-
-	for permutation in permutations {
-		// Setting for A is 0.
-
-		// Program takes inputs in this order: phase setting, value.
-		let [mut a, mut b, mut c, mut d, mut e] = [
-			Intcode::from(program.clone()),
-			Intcode::from(program.clone()),
-			Intcode::from(program.clone()),
-			Intcode::from(program.clone()),
-			Intcode::from(program.clone()),
-		];
-
-		a.input(permutation[0]);
-		a.input(0);
-		b.input(permutation[1]);
-		c.input(permutation[2]);
-		d.input(permutation[3]);
-		e.input(permutation[4]);
-
-		// Run A, feed into B
-		let mut a = a.run();
-		b.input(a.output().expect("expected output from A"));
-
-		// Run B, feed into C
-		let mut b = b.run();
-		c.input(b.output().expect("expected output from B"));
-
-		// Run C, feed into D
-		let mut c = c.run();
-		d.input(c.output().expect("expected output from C"));
-
-		// Run D, feed into E
-		let mut d = d.run();
-		e.input(d.output().expect("expected output from D"));
+/// Runs a chain of amplifiers, one per phase setting, starting from a signal of 0.  With
+/// `feedback`, the last amplifier's output is fed back into the first until they halt.  Returns the
+/// last signal sent to the thrusters.
+fn amplify(program: &Intermediate, phases: &[i64], feedback: bool) -> Option<Output> {
+	// Each amplifier takes its phase setting as its first input.
+	let amplifiers = phases
+		.iter()
+		.map(|&phase| {
+			let mut amplifier = Intcode::from(program);
+			amplifier.input(phase);
+			amplifier
+		})
+		.collect();
 
-		let mut e = e.run();
-		let output = e.output().expect("expected output from E");
+	let topology = if feedback {
+		Chain::ring()
+	} else {
+		Chain::pipeline()
+	};
 
-		println!("Setting {permutation:?} produces output {output}");
+	let mut network = Network::new(amplifiers, topology);
+	network.input(0, 0);
 
-		if let Some(max_so_far) = max {
-			if output > max_so_far {
-				max.replace(output);
-			}
-		} else {
-			max.replace(output);
-		}
+	match network.run() {
+		Ok(Outcome::Halted) => network.topology().last_output(),
+		_ => None,
 	}
+}
 
-	max
+#[must_use]
+pub fn part_one(program: &Intermediate) -> Option<Output> {
+	(0..5)
+		.permutations(5)
+		.filter_map(|phases| amplify(program, &phases, false))
+		.max()
 }
 
-#[allow(clippy::many_single_char_names)]
 #[must_use]
 pub fn part_two(program: &Intermediate) -> Option<Output> {
-	let permutations = (5..10).permutations(5);
-
-	let mut max: Option<i32> = None;
-
-	for permutation in permutations {
-		// Setting for A is 0.
-
-		// Program takes inputs in this order: phase setting, value.
-
-		let [mut a, mut b, mut c, mut d, mut e] = [
-			Intcode::from(program.clone()),
-			Intcode::from(program.clone()),
-			Intcode::from(program.clone()),
-			Intcode::from(program.clone()),
-			Intcode::from(program.clone()),
-		];
-
-		// Seed each of the amplifiers with their settings...
-		a.input(permutation[0]);
-		a.input(0);
-		b.input(permutation[1]);
-		c.input(permutation[2]);
-		d.input(permutation[3]);
-		e.input(permutation[4]);
-
-		let mut to_thrusters = None;
-
-		loop {
-			// Run A, feed into B
-			let a_output = a.run_til_next_output().output();
-
-			if let Some(a_output) = a_output {
-				b.input(a_output);
-			} else {
-				break;
-			}
-
-			// Run B, feed into C
-			let b_output = b
-				.run_til_next_output()
-				.output()
-				.expect("expected output from B");
-			c.input(b_output);
-
-			// Run C, feed into D
-			let c_output = c
-				.run_til_next_output()
-				.output()
-				.expect("expected output from C");
-			d.input(c_output);
-
-			// Run D, feed into E
-			let d_output = d
-				.run_til_next_output()
-				.output()
-				.expect("expected output from D");
-			e.input(d_output);
-
-			let e_output = e
-				.run_til_next_output()
-				.output()
-				.expect("expected output from E");
-			let output = e_output;
-
-			to_thrusters = Some(output);
-
-			a.input(output);
-		}
-
-		let output = to_thrusters.expect("loop through thrusters should have occurred at least once!");
-
-		if let Some(max_so_far) = max {
-			if output > max_so_far {
-				max.replace(output);
-			}
-		} else {
-			max.replace(output);
-		}
-	}
-
-	max
+	(5..10)
+		.permutations(5)
+		.filter_map(|phases| amplify(program, &phases, true))
+		.max()
 }
 
 daocutil::test_example!(
@@ -175,3 +73,11 @@ daocutil::test_example!(
 	"3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10",
 	Some(18_216)
 );
+
+daocutil::test_example!(
+	part_one_43210,
+	parse,
+	part_one,
+	"3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0",
+	Some(43_210)
+);
@@ -1,17 +1,28 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use crate::intcode::{Intcode, Program};
+
+pub type Intermediate = Program;
+pub type Output = i64;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	Ok(input.parse()?)
+}
+
+fn boost(program: &Intermediate, mode: i64) -> Option<Output> {
+	let mut intcode = Intcode::from(program);
+	intcode.input(mode);
+	let outputs = intcode.run_to_halt().ok()?;
+
+	// Any output before the last is an opcode the program found to be malfunctioning.
+	outputs.last().copied()
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(program: &Intermediate) -> Option<Output> {
+	boost(program, 1)
 }
 
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(program: &Intermediate) -> Option<Output> {
+	boost(program, 2)
 }
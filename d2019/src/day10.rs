@@ -1,17 +1,214 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use core::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use daocutil::parse::ParseError;
+
+/// An asteroid's position, with `x` increasing to the right and `y` increasing downwards.
+pub type Position = (i32, i32);
+
+pub type Intermediate = Vec<Position>;
+pub type Output = usize;
+
+/// The direction from one asteroid to another, as the smallest whole-number step which lands on
+/// both.  Asteroids in the same direction share a line of sight, so only the nearest is visible.
+///
+/// Directions are ordered clockwise starting from straight up, which is the order in which a laser
+/// rotating clockwise from up would sweep across them.  The ordering is exact, with no angles
+/// involved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Direction {
+	dx: i32,
+	dy: i32,
+}
+
+impl Direction {
+	/// The direction from `from` to `to`, along with how many steps in that direction it is.
+	fn between(from: Position, to: Position) -> (Self, i32) {
+		let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+		let steps = num_integer::gcd(dx, dy);
+
+		(
+			Self {
+				dx: dx / steps,
+				dy: dy / steps,
+			},
+			steps,
+		)
+	}
+
+	/// Whether the direction is in the half of the circle swept first: from straight up, through
+	/// the right, to just short of straight down.
+	fn first_half(self) -> bool {
+		self.dx > 0 || (self.dx == 0 && self.dy < 0)
+	}
+}
+
+impl Ord for Direction {
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.first_half().cmp(&self.first_half()).then_with(|| {
+			// Within a half, `other` is further clockwise exactly when the cross product is
+			// positive.  With `y` pointing down, positive means clockwise.
+			let cross =
+				i64::from(self.dx) * i64::from(other.dy) - i64::from(self.dy) * i64::from(other.dx);
+			0.cmp(&cross)
+		})
+	}
+}
+
+impl PartialOrd for Direction {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	let rows = daocutil::parse::lines(input, |line| {
+		line
+			.char_indices()
+			.filter_map(|(x, c)| match c {
+				// Some examples mark the monitoring station's asteroid with an `X`.
+				'#' | 'X' => Some(Ok(x)),
+				'.' => None,
+				_ => Some(Err(ParseError::invalid(
+					x + 1,
+					&c.to_string(),
+					"expected `#` or `.`",
+				))),
+			})
+			.collect::<Result<Vec<_>, _>>()
+	})?;
+
+	let mut asteroids = Vec::new();
+
+	for (y, xs) in rows.into_iter().enumerate() {
+		for x in xs {
+			asteroids.push((i32::try_from(x)?, i32::try_from(y)?));
+		}
+	}
+
+	Ok(asteroids)
+}
+
+/// Every other asteroid, grouped by direction from `station` and nearest first.
+fn lines_of_sight(asteroids: &[Position], station: Position) -> BTreeMap<Direction, Vec<Position>> {
+	let mut lines: BTreeMap<Direction, Vec<(i32, Position)>> = BTreeMap::new();
+
+	for &asteroid in asteroids.iter().filter(|&&asteroid| asteroid != station) {
+		let (direction, steps) = Direction::between(station, asteroid);
+		lines.entry(direction).or_default().push((steps, asteroid));
+	}
+
+	lines
+		.into_iter()
+		.map(|(direction, mut line)| {
+			line.sort_unstable();
+			(
+				direction,
+				line.into_iter().map(|(_, asteroid)| asteroid).collect(),
+			)
+		})
+		.collect()
+}
+
+/// The asteroid from which the most other asteroids can be seen, and how many that is.
+fn best_station(asteroids: &[Position]) -> Option<(Position, usize)> {
+	asteroids
+		.iter()
+		.map(|&station| (station, lines_of_sight(asteroids, station).len()))
+		.max_by_key(|&(_, visible)| visible)
+}
+
+/// The order in which a laser at `station`, starting pointing up and rotating clockwise, destroys
+/// every other asteroid.  Each pass only destroys the nearest asteroid in each direction.
+fn vaporization_order(asteroids: &[Position], station: Position) -> Vec<Position> {
+	let lines: Vec<Vec<Position>> = lines_of_sight(asteroids, station).into_values().collect();
+	let passes = lines.iter().map(Vec::len).max().unwrap_or_default();
+
+	(0..passes)
+		.flat_map(|pass| lines.iter().filter_map(move |line| line.get(pass).copied()))
+		.collect()
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(asteroids: &Intermediate) -> Option<Output> {
+	let (_, visible) = best_station(asteroids)?;
+
+	Some(visible)
 }
 
+daocutil::generate_example_tests!(
+	parse, part_one,
+	part_one_example_1 | include_str!("examples/day10-1") => Some(8),
+	part_one_example_2 | include_str!("examples/day10-2") => Some(33),
+	part_one_example_3 | include_str!("examples/day10-3") => Some(35),
+	part_one_example_4 | include_str!("examples/day10-4") => Some(41),
+	part_one_example_5 | include_str!("examples/day10-5") => Some(210),
+);
+
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(asteroids: &Intermediate) -> Option<Output> {
+	let (station, _) = best_station(asteroids)?;
+	let (x, y) = *vaporization_order(asteroids, station).get(199)?;
+
+	usize::try_from(x * 100 + y).ok()
+}
+
+daocutil::test_example!(
+	part_two_example,
+	parse,
+	part_two,
+	include_str!("examples/day10-5"),
+	Some(802)
+);
+
+#[test]
+fn best_station_example() {
+	let asteroids = parse(include_str!("examples/day10-5")).unwrap();
+
+	assert_eq!(best_station(&asteroids), Some(((11, 13), 210)));
+}
+
+#[test]
+fn vaporization_order_example() {
+	let asteroids = parse(include_str!("examples/day10-6")).unwrap();
+	let order = vaporization_order(&asteroids, (8, 3));
+
+	assert_eq!(
+		order[..9],
+		[
+			(8, 1),
+			(9, 0),
+			(9, 1),
+			(10, 0),
+			(9, 2),
+			(11, 1),
+			(12, 1),
+			(11, 2),
+			(15, 1)
+		]
+	);
+
+	let order = vaporization_order(&parse(include_str!("examples/day10-5")).unwrap(), (11, 13));
+
+	assert_eq!(order[0], (11, 12));
+	assert_eq!(order[1], (12, 1));
+	assert_eq!(order[298], (11, 1));
+}
+
+#[test]
+fn directions_sweep_clockwise() {
+	let directions = [
+		(0, -1),
+		(1, -2),
+		(1, 0),
+		(1, 1),
+		(0, 1),
+		(-1, 1),
+		(-1, 0),
+		(-1, -3),
+	]
+	.map(|(dx, dy)| Direction { dx, dy });
+
+	assert!(directions.windows(2).all(|pair| pair[0] < pair[1]));
 }
@@ -1,17 +1,64 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use core::fmt::{self, Display, Formatter};
+
+use daocutil::viz::{self, Glyph};
+
+use crate::intcode::{
+	robot::{Panels, Robot},
+	Intcode, Program,
+};
+
+pub type Intermediate = Program;
+
+#[derive(Debug, PartialEq)]
+pub enum Output {
+	Painted(usize),
+	/// The registration identifier, or a picture of it if it couldn't be read.
+	Registration(String),
+}
+
+impl Display for Output {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Painted(count) => write!(f, "{count}"),
+			Self::Registration(text) if text.contains('\n') => write!(f, "\n{text}"),
+			Self::Registration(text) => write!(f, "{text}"),
+		}
+	}
+}
+
+const WHITE: i64 = 1;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	Ok(input.parse()?)
+}
+
+fn paint(program: &Intermediate, mut panels: Panels) -> Option<Panels> {
+	Robot::new(Intcode::from(program)).run(&mut panels).ok()?;
+
+	Some(panels)
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(program: &Intermediate) -> Option<Output> {
+	let panels = paint(program, Panels::new())?;
+
+	Some(Output::Painted(panels.len()))
 }
 
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(program: &Intermediate) -> Option<Output> {
+	let panels = paint(program, Panels::from([((0, 0), WHITE)]))?;
+
+	let picture = viz::render_sparse(
+		panels
+			.into_iter()
+			.filter(|&(_, color)| color == WHITE)
+			.map(|(position, _)| (position, Glyph::from('#'))),
+		Glyph::from(' '),
+	);
+
+	Some(Output::Registration(
+		daocutil::ocr::decode_str(&picture).unwrap_or(picture),
+	))
 }
@@ -1,17 +1,88 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use daocutil::{
+	cycle::{self, Cycle},
+	particles::{self, Particle},
+};
+
+pub type Moon = Particle<3>;
+
+pub type Intermediate = Vec<Moon>;
+pub type Output = usize;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	let moons = daocutil::parse::lines(input, |line| {
+		let (x, y, z) = daocutil::scan!(line, "<x={}, y={}, z={}>", i64, i64, i64)?;
+		Ok(Moon::at_rest([x, y, z]))
+	})?;
+
+	Ok(moons)
+}
+
+/// Each pair of moons pulls on each other by one unit along every axis on which they differ.
+fn gravity<const D: usize>(idx: usize, moons: &[Particle<D>]) -> [i64; D] {
+	let mut pull = [0; D];
+
+	for other in moons {
+		for (axis, pull) in pull.iter_mut().enumerate() {
+			*pull += (other.position[axis] - moons[idx].position[axis]).signum();
+		}
+	}
+
+	pull
+}
+
+fn tick<const D: usize>(moons: &[Particle<D>]) -> Vec<Particle<D>> {
+	let mut moons = moons.to_vec();
+	particles::step(&mut moons, gravity);
+	moons
+}
+
+fn energy_after(moons: &[Moon], steps: usize) -> Option<Output> {
+	let moons = (0..steps).fold(moons.to_vec(), |moons, _| tick(&moons));
+
+	let energy: i64 = moons
+		.iter()
+		.map(|moon| moon.potential_energy() * moon.kinetic_energy())
+		.sum();
+
+	energy.try_into().ok()
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(moons: &Intermediate) -> Option<Output> {
+	energy_after(moons, 1000)
+}
+
+#[test]
+fn energy_examples() {
+	let moons = parse(include_str!("examples/day12-1")).unwrap();
+	assert_eq!(energy_after(&moons, 10), Some(179));
+
+	let moons = parse(include_str!("examples/day12-2")).unwrap();
+	assert_eq!(energy_after(&moons, 100), Some(1940));
 }
 
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(moons: &Intermediate) -> Option<Output> {
+	// Gravity along one axis only depends on positions along that axis, so each axis repeats on its
+	// own schedule, and the whole system repeats once they all line up.
+	let cycles: Vec<Cycle> = (0..3)
+		.map(|axis| {
+			let moons: Vec<Particle<1>> = moons.iter().map(|moon| moon.axis(axis)).collect();
+			cycle::brent(moons, |moons| tick(moons))
+		})
+		.collect();
+
+	let mu = cycles.iter().map(|cycle| cycle.mu).max()?;
+	let lambda = cycles
+		.iter()
+		.fold(1, |lambda, cycle| num_integer::lcm(lambda, cycle.lambda));
+
+	Some(mu + lambda)
 }
+
+daocutil::generate_example_tests!(
+	parse, part_two,
+	part_two_example_1 | include_str!("examples/day12-1") => Some(2772),
+	part_two_example_2 | include_str!("examples/day12-2") => Some(4_686_774_924),
+);
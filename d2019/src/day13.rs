@@ -1,17 +1,40 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use daocutil::viz::Animation;
+
+use crate::intcode::{arcade::Arcade, Intcode, Program};
+
+pub type Intermediate = Program;
+pub type Output = i64;
+
+/// Writing this to address 0 sets the game to free play.
+const QUARTERS: (usize, i64) = (0, 2);
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	Ok(input.parse()?)
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(program: &Intermediate) -> Option<Output> {
+	let mut arcade = Arcade::new(Intcode::from(program));
+
+	// Without any quarters the game only draws the screen, and never reads the joystick.
+	if !arcade.update().ok()? {
+		return None;
+	}
+
+	arcade.blocks().try_into().ok()
 }
 
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(program: &Intermediate) -> Option<Output> {
+	let mut arcade = Arcade::new(Intcode::from(program.patched(&[QUARTERS])));
+	let mut animation = Animation::from_flag();
+
+	arcade
+		.autoplay(|arcade| {
+			if let Some(animation) = &mut animation {
+				animation.frame(&arcade.frame());
+			}
+		})
+		.ok()
 }
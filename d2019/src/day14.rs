@@ -1,17 +1,307 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use core::str::FromStr;
+use std::collections::HashMap;
+
+use daocutil::parse::ParseError;
+
+const ORE: &str = "ORE";
+const FUEL: &str = "FUEL";
+
+/// The ORE available for part two.
+const CARGO: u64 = 1_000_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reaction {
+	pub inputs: Vec<(String, u64)>,
+	pub output: (String, u64),
+}
+
+/// Parses a `quantity CHEMICAL` term which starts `offset` bytes into its line.  Quantities must
+/// be positive, since a reaction producing nothing would never finish producing what's needed.
+fn term(offset: usize, term: &str) -> Result<(String, u64), ParseError> {
+	let (quantity, chemical) =
+		daocutil::scan!(term, "{} {}", u64, String).map_err(|error| ParseError {
+			column: error.column + offset,
+			..error
+		})?;
+
+	if quantity == 0 {
+		return Err(ParseError::invalid(
+			offset + 1,
+			"0",
+			"quantities must be positive",
+		));
+	}
+
+	Ok((chemical, quantity))
+}
+
+impl FromStr for Reaction {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, ParseError> {
+		let fields = daocutil::parse::scan_fields(s, "{} => {}")?;
+		let (inputs_column, inputs) = fields[0];
+		let (output_column, output) = fields[1];
+
+		let mut offset = inputs_column - 1;
+		let mut terms = Vec::new();
+
+		for input in inputs.split(", ") {
+			terms.push(term(offset, input)?);
+			offset += input.len() + ", ".len();
+		}
+
+		Ok(Self {
+			inputs: terms,
+			output: term(output_column - 1, output)?,
+		})
+	}
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ReactionError {
+	#[error("nothing produces {0}")]
+	UnknownChemical(String),
+	#[error("{0} is produced by more than one reaction")]
+	DuplicateRecipe(String),
+	#[error("{0} is needed to produce itself")]
+	Cycle(String),
+}
+
+/// What it took to produce some FUEL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Production {
+	pub ore: u64,
+	/// Chemicals produced but not used, since reactions only run in whole batches.
+	pub leftovers: HashMap<String, u64>,
+}
+
+#[derive(Debug)]
+pub struct Nanofactory {
+	reactions: HashMap<String, Reaction>,
+	/// Every chemical needed for FUEL, ordered so that each comes before all of its inputs.
+	order: Vec<String>,
+}
+
+impl Nanofactory {
+	/// # Errors
+	///
+	/// Fails if two reactions produce the same chemical, an input is never produced, or a chemical
+	/// is needed (however indirectly) to produce itself.
+	pub fn new(reactions: Vec<Reaction>) -> Result<Self, ReactionError> {
+		let mut by_output = HashMap::new();
+
+		for reaction in reactions {
+			let chemical = reaction.output.0.clone();
+
+			if by_output.insert(chemical.clone(), reaction).is_some() {
+				return Err(ReactionError::DuplicateRecipe(chemical));
+			}
+		}
+
+		let mut factory = Self {
+			reactions: by_output,
+			order: Vec::new(),
+		};
+
+		let mut visiting = Vec::new();
+		factory.visit(FUEL, &mut visiting)?;
+		factory.order.reverse();
+
+		Ok(factory)
+	}
+
+	/// Adds `chemical` to the order after all of its inputs (depth-first, so the order ends up
+	/// reversed), with `visiting` holding the chemicals whose inputs are being visited.
+	fn visit(&mut self, chemical: &str, visiting: &mut Vec<String>) -> Result<(), ReactionError> {
+		if chemical == ORE || self.order.iter().any(|done| done == chemical) {
+			return Ok(());
+		}
+
+		if visiting.iter().any(|open| open == chemical) {
+			return Err(ReactionError::Cycle(chemical.to_string()));
+		}
+
+		let inputs: Vec<String> = self
+			.reactions
+			.get(chemical)
+			.ok_or_else(|| ReactionError::UnknownChemical(chemical.to_string()))?
+			.inputs
+			.iter()
+			.map(|(input, _)| input.clone())
+			.collect();
+
+		visiting.push(chemical.to_string());
+
+		for input in inputs {
+			self.visit(&input, visiting)?;
+		}
+
+		visiting.pop();
+		self.order.push(chemical.to_string());
+
+		Ok(())
+	}
+
+	/// Works out how much ORE it takes to produce `fuel` FUEL, or `None` if the amounts involved
+	/// don't fit in a `u64`.
+	pub fn produce(&self, fuel: u64) -> Option<Production> {
+		let mut needed: HashMap<&str, u64> = HashMap::from([(FUEL, fuel)]);
+		let mut leftovers = HashMap::new();
+
+		// Everything which needs a chemical comes before it in the order, so by the time a chemical
+		// is reached, everything needing it has added to how much is needed.
+		for chemical in &self.order {
+			let need = needed.remove(chemical.as_str()).unwrap_or_default();
+			let reaction = &self.reactions[chemical];
+			let batch = reaction.output.1;
+			let batches = need.div_ceil(batch);
+			let made = batches.checked_mul(batch)?;
+
+			if made > need {
+				leftovers.insert(chemical.clone(), made - need);
+			}
+
+			for (input, quantity) in &reaction.inputs {
+				let total = needed.entry(input).or_default();
+				*total = total.checked_add(batches.checked_mul(*quantity)?)?;
+			}
+		}
+
+		Some(Production {
+			ore: needed.get(ORE).copied().unwrap_or_default(),
+			leftovers,
+		})
+	}
+
+	/// The most FUEL which can be produced from `ore` ORE.
+	pub fn max_fuel(&self, ore: u64) -> u64 {
+		let fits = |fuel| {
+			self
+				.produce(fuel)
+				.is_some_and(|production| production.ore <= ore)
+		};
+
+		if !fits(1) {
+			return 0;
+		}
+
+		// Double until too much, then binary search between the last two guesses.
+		let mut low = 1;
+		let mut high = 2;
+
+		while fits(high) {
+			low = high;
+
+			// Doubling can't go past the largest amount there is, so search up to that instead.
+			let Some(next) = high.checked_mul(2) else {
+				if fits(u64::MAX) {
+					return u64::MAX;
+				}
+
+				high = u64::MAX;
+				break;
+			};
+
+			high = next;
+		}
+
+		while high - low > 1 {
+			let mid = low + (high - low) / 2;
+
+			if fits(mid) {
+				low = mid;
+			} else {
+				high = mid;
+			}
+		}
+
+		low
+	}
+}
+
+pub type Intermediate = Nanofactory;
+pub type Output = u64;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	let reactions = daocutil::parse::lines(input, str::parse)?;
+
+	Ok(Nanofactory::new(reactions)?)
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(factory: &Intermediate) -> Option<Output> {
+	Some(factory.produce(1)?.ore)
 }
 
+daocutil::generate_example_tests!(
+	parse, part_one,
+	part_one_example_1 | include_str!("examples/day14-1") => Some(31),
+	part_one_example_2 | include_str!("examples/day14-2") => Some(165),
+	part_one_example_3 | include_str!("examples/day14-3") => Some(13_312),
+	part_one_example_4 | include_str!("examples/day14-4") => Some(180_697),
+	part_one_example_5 | include_str!("examples/day14-5") => Some(2_210_736),
+);
+
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(factory: &Intermediate) -> Option<Output> {
+	Some(factory.max_fuel(CARGO))
+}
+
+daocutil::generate_example_tests!(
+	parse, part_two,
+	part_two_example_3 | include_str!("examples/day14-3") => Some(82_892_753),
+	part_two_example_4 | include_str!("examples/day14-4") => Some(5_586_022),
+	part_two_example_5 | include_str!("examples/day14-5") => Some(460_664),
+);
+
+#[test]
+fn leftovers() {
+	let factory = parse(include_str!("examples/day14-1")).unwrap();
+	let production = factory.produce(1).unwrap();
+
+	// 28 A are needed, so three batches of ten leave two over.
+	assert_eq!(production.leftovers, HashMap::from([("A".to_string(), 2)]));
+}
+
+#[test]
+fn malformed_reactions() {
+	let error = "7 A, x B => 1 C".parse::<Reaction>().unwrap_err();
+	assert_eq!(error.column, 6);
+
+	let error = "1 ORE => 0 A".parse::<Reaction>().unwrap_err();
+	assert_eq!(error.column, 10);
+	assert_eq!(
+		parse("0 ORE => 1 FUEL").unwrap_err().to_string(),
+		"line 1, column 1: invalid `0`: quantities must be positive"
+	);
+
+	assert_eq!(
+		parse("1 ORE => 1 A\n1 A, 1 B => 1 FUEL")
+			.unwrap_err()
+			.to_string(),
+		"nothing produces B"
+	);
+	assert_eq!(
+		parse("1 FUEL => 1 A\n1 A => 1 FUEL")
+			.unwrap_err()
+			.to_string(),
+		"FUEL is needed to produce itself"
+	);
+	assert_eq!(
+		parse("1 ORE => 1 A\n2 ORE => 1 A\n1 A => 1 FUEL")
+			.unwrap_err()
+			.to_string(),
+		"A is produced by more than one reaction"
+	);
+}
+
+#[test]
+fn huge_amounts_of_ore() {
+	let factory = parse("1 ORE => 10 FUEL").unwrap();
+
+	// Ten FUEL are made at a time, so the most which can be made is the largest multiple of ten.
+	assert_eq!(factory.max_fuel(u64::MAX), u64::MAX - u64::MAX % 10);
+	assert!(factory.produce(u64::MAX).is_none());
 }
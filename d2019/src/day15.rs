@@ -1,17 +1,35 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use crate::intcode::{
+	droid::{Droid, Map},
+	Intcode, Program,
+};
+
+pub type Intermediate = Program;
+pub type Output = usize;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	Ok(input.parse()?)
+}
+
+/// Maps the whole sealed area, returning the map along with where the oxygen system is.
+fn explore(program: &Intermediate) -> Option<(Map, (i32, i32))> {
+	let map = Map::explore(Droid::new(Intcode::from(program))).ok()?;
+	let oxygen = map.oxygen()?;
+
+	Some((map, oxygen))
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(program: &Intermediate) -> Option<Output> {
+	let (map, oxygen) = explore(program)?;
+
+	map.distances((0, 0)).get(&oxygen).copied()
 }
 
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(program: &Intermediate) -> Option<Output> {
+	let (map, oxygen) = explore(program)?;
+
+	// Oxygen spreads one step a minute, so filling the area takes as long as the furthest walk.
+	map.distances(oxygen).into_values().max()
 }
@@ -1,17 +1,180 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use daocutil::parse::ParseError;
+
+pub type Intermediate = Vec<u8>;
+pub type Output = String;
+
+const PHASES: usize = 100;
+const REPEATS: usize = 10_000;
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageError {
+	#[error("signal is too short to hold a message offset")]
+	NoOffset,
+	/// The shortcut only works in the second half of the signal, where every pattern is zeros
+	/// followed by ones.
+	#[error("message offset {offset} is in the first half of the {len}-digit signal")]
+	FirstHalf { offset: usize, len: usize },
+	#[error("message offset {offset} runs past the end of the {len}-digit signal")]
+	PastEnd { offset: usize, len: usize },
+}
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	let digits = input
+		.trim()
+		.char_indices()
+		.map(|(idx, c)| {
+			c.to_digit(10)
+				.and_then(|digit| u8::try_from(digit).ok())
+				.ok_or_else(|| ParseError::invalid(idx + 1, &c.to_string(), "not a digit"))
+		})
+		.collect::<Result<_, _>>()?;
+
+	Ok(digits)
+}
+
+fn to_string(digits: &[u8]) -> String {
+	digits.iter().map(u8::to_string).collect()
+}
+
+/// Runs one phase.  Output digit `i` uses the pattern `0, 1, 0, -1` with each value repeated
+/// `i + 1` times, skipping the very first value, so it adds up runs of `i + 1` input digits
+/// starting at `i` and subtracts the runs which start `2 * (i + 1)` later.  Each run is summed
+/// from prefix sums, so the whole phase takes roughly `n log n` steps.
+fn phase(signal: &[u8]) -> Vec<u8> {
+	let mut prefix = vec![0_i64; signal.len() + 1];
+
+	for (idx, &digit) in signal.iter().enumerate() {
+		prefix[idx + 1] = prefix[idx] + i64::from(digit);
+	}
+
+	let run = |start: usize, len: usize| {
+		let end = (start + len).min(signal.len());
+		prefix[end] - prefix[start.min(end)]
+	};
+
+	(0..signal.len())
+		.map(|idx| {
+			let width = idx + 1;
+			let total: i64 = (idx..signal.len())
+				.step_by(4 * width)
+				.map(|start| run(start, width) - run(start + 2 * width, width))
+				.sum();
+
+			u8::try_from(total.abs() % 10).unwrap()
+		})
+		.collect()
+}
+
+fn fft(signal: &[u8], phases: usize) -> Vec<u8> {
+	(0..phases).fold(signal.to_vec(), |signal, _| phase(&signal))
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(signal: &Intermediate) -> Option<Output> {
+	let output = fft(signal, PHASES);
+
+	Some(to_string(output.get(..8)?))
+}
+
+#[test]
+fn phase_example() {
+	assert_eq!(to_string(&fft(&parse("12345678").unwrap(), 4)), "01029498");
+}
+
+daocutil::generate_example_tests!(
+	parse, part_one,
+	part_one_example_1 | "80871224585914546619083218645595" => Some("24176176".to_string()),
+	part_one_example_2 | "19617804207202209144916044189917" => Some("73745418".to_string()),
+	part_one_example_3 | "69317163492948606335995924319873" => Some("52432133".to_string()),
+);
+
+/// Finds the eight-digit message in the real signal, which is `signal` repeated ten thousand
+/// times, at the offset given by the signal's first seven digits.
+///
+/// In the second half of the signal, each pattern is all zeros up to the digit's own position and
+/// all ones from there on, so each new digit is just the sum of the digits from its position to
+/// the end.  Digits in the second half never depend on those before them, so only the part from
+/// the offset onwards needs computing.
+///
+/// # Errors
+///
+/// Fails if the signal is too short to hold an offset, or the offset is somewhere the shortcut
+/// doesn't work.
+pub fn message(signal: &[u8]) -> Result<String, MessageError> {
+	let offset = signal
+		.get(..7)
+		.ok_or(MessageError::NoOffset)?
+		.iter()
+		.fold(0, |offset, &digit| offset * 10 + usize::from(digit));
+
+	let len = signal.len() * REPEATS;
+
+	if offset < len / 2 {
+		return Err(MessageError::FirstHalf { offset, len });
+	}
+
+	if offset + 8 > len {
+		return Err(MessageError::PastEnd { offset, len });
+	}
+
+	let mut tail: Vec<u8> = (offset..len)
+		.map(|idx| signal[idx % signal.len()])
+		.collect();
+
+	for _ in 0..PHASES {
+		let mut sum = 0;
+
+		for digit in tail.iter_mut().rev() {
+			sum = (sum + *digit) % 10;
+			*digit = sum;
+		}
+	}
+
+	Ok(to_string(&tail[..8]))
 }
 
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(signal: &Intermediate) -> Option<Output> {
+	// Solvers only report whether there's an answer, so the reason there isn't one is dropped
+	// here.  Call `message` directly to find out which `MessageError` it was.
+	message(signal).ok()
+}
+
+daocutil::generate_example_tests!(
+	parse, part_two,
+	part_two_example_1 | "03036732577212944063491565474664" => Some("84462026".to_string()),
+	part_two_example_2 | "02935109699940807407585447034323" => Some("78725270".to_string()),
+	part_two_example_3 | "03081770884921959731165446850517" => Some("53553731".to_string()),
+);
+
+#[test]
+fn refuses_early_offsets() {
+	assert_eq!(
+		message(&parse("00000010000000000000000000000000").unwrap()),
+		Err(MessageError::FirstHalf {
+			offset: 1,
+			len: 320_000
+		})
+	);
+	assert_eq!(
+		message(&parse("80871224585914546619083218645595").unwrap()),
+		Err(MessageError::PastEnd {
+			offset: 8_087_122,
+			len: 320_000
+		})
+	);
+	assert_eq!(message(&[1, 2, 3]), Err(MessageError::NoOffset));
+}
+
+#[test]
+fn rejects_non_digits() {
+	assert_eq!(
+		parse("12a4")
+			.unwrap_err()
+			.downcast::<ParseError>()
+			.unwrap()
+			.column,
+		3
+	);
 }
@@ -1,17 +1,319 @@
-pub type Intermediate = ();
+use core::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use daocutil::parse::ParseError;
+
+/// A position in the vault, with `x` increasing to the right and `y` increasing downwards.
+pub type Position = (i32, i32);
+
+/// A set of keys, with bit `n` standing for the `n`th letter of the alphabet.
+type Keys = u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+	Wall,
+	Open,
+	Entrance,
+	Key(u8),
+	Door(u8),
+}
+
+impl Cell {
+	fn from_char(c: char) -> Option<Self> {
+		match c {
+			'#' => Some(Self::Wall),
+			'.' => Some(Self::Open),
+			'@' => Some(Self::Entrance),
+			'a'..='z' => Some(Self::Key(c as u8 - b'a')),
+			'A'..='Z' => Some(Self::Door(c as u8 - b'A')),
+			_ => None,
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultError {
+	#[error("the vault has no entrance")]
+	NoEntrance,
+	#[error("there is more than one key `{0}`")]
+	DuplicateKey(char),
+}
+
+#[derive(Debug, Clone)]
+pub struct Vault {
+	cells: Vec<Vec<Cell>>,
+	entrances: Vec<Position>,
+	keys: Vec<(u8, Position)>,
+}
+
+/// The shortest walk from one place to a key, ignoring doors.  The vault's passages form a maze
+/// without loops, so this is the only sensible way there and the doors on it must be opened first.
+#[derive(Debug, Clone, Copy)]
+struct Walk {
+	key: u8,
+	steps: u32,
+	/// The doors on the way, which need their keys to have been collected.
+	doors: Keys,
+	/// The keys on the way, which get collected in passing.
+	keys: Keys,
+}
+
+/// Where each robot is (by index into [`Vault::walks`]'s places) and which keys have been
+/// collected.
+type State = (Vec<usize>, Keys);
+
+impl Vault {
+	/// # Errors
+	///
+	/// Fails if the vault has no entrance or two keys share a letter.
+	pub fn new(cells: Vec<Vec<Cell>>) -> Result<Self, VaultError> {
+		let mut entrances = Vec::new();
+		let mut keys = Vec::new();
+
+		for (y, row) in (0..).zip(&cells) {
+			for (x, &cell) in (0..).zip(row) {
+				match cell {
+					Cell::Entrance => entrances.push((x, y)),
+					Cell::Key(key) => {
+						if keys.iter().any(|&(other, _)| other == key) {
+							return Err(VaultError::DuplicateKey(char::from(b'a' + key)));
+						}
+
+						keys.push((key, (x, y)));
+					}
+					_ => {}
+				}
+			}
+		}
+
+		if entrances.is_empty() {
+			return Err(VaultError::NoEntrance);
+		}
+
+		Ok(Self {
+			cells,
+			entrances,
+			keys,
+		})
+	}
+
+	fn cell(&self, (x, y): Position) -> Cell {
+		usize::try_from(y)
+			.ok()
+			.zip(usize::try_from(x).ok())
+			.and_then(|(y, x)| self.cells.get(y)?.get(x))
+			.copied()
+			.unwrap_or(Cell::Wall)
+	}
+
+	fn set(&mut self, (x, y): Position, cell: Cell) {
+		self.cells[usize::try_from(y).unwrap()][usize::try_from(x).unwrap()] = cell;
+	}
+
+	/// Splits a vault with a single entrance into four, walling off the entrance and the spaces
+	/// around it and putting an entrance in each diagonal corner.  Returns `None` unless the
+	/// entrance is surrounded by open space.
+	#[must_use]
+	pub fn split(&self) -> Option<Self> {
+		let &[(x, y)] = self.entrances.as_slice() else {
+			return None;
+		};
+
+		if daocutil::get(&(x, y)).any(|position| self.cell(position) != Cell::Open) {
+			return None;
+		}
+
+		let mut vault = self.clone();
+
+		for position in daocutil::get_no_diags(&(x, y)).chain([(x, y)]) {
+			vault.set(position, Cell::Wall);
+		}
+
+		let entrances = [(-1, -1), (1, -1), (-1, 1), (1, 1)].map(|(dx, dy)| (x + dx, y + dy));
+
+		for entrance in entrances {
+			vault.set(entrance, Cell::Entrance);
+		}
+
+		vault.entrances = entrances.to_vec();
+
+		Some(vault)
+	}
+
+	/// Every key reachable from `from`, breadth-first.
+	fn walks_from(&self, from: Position) -> Vec<Walk> {
+		let mut seen = HashSet::from([from]);
+		let mut queue = VecDeque::from([(from, 0, 0, 0)]);
+		let mut walks = Vec::new();
+
+		while let Some((position, steps, mut doors, mut keys)) = queue.pop_front() {
+			match self.cell(position) {
+				Cell::Door(door) => doors |= 1 << door,
+				Cell::Key(key) if position != from => {
+					walks.push(Walk {
+						key,
+						steps,
+						doors,
+						keys,
+					});
+					keys |= 1 << key;
+				}
+				_ => {}
+			}
+
+			for next in daocutil::get_no_diags(&position) {
+				if self.cell(next) != Cell::Wall && seen.insert(next) {
+					queue.push_back((next, steps + 1, doors, keys));
+				}
+			}
+		}
+
+		walks
+	}
+
+	/// The walks to every key from each entrance and then from each key, in that order, so that
+	/// places are numbered with the entrances first.
+	fn walks(&self) -> Vec<Vec<Walk>> {
+		self
+			.entrances
+			.iter()
+			.chain(self.keys.iter().map(|(_, position)| position))
+			.map(|&position| self.walks_from(position))
+			.collect()
+	}
+
+	/// The fewest steps for the robots, one at each entrance, to collect every key.  Only one robot
+	/// moves at a time, and a robot can only walk through a door once its key has been collected
+	/// by any of them.
+	#[must_use]
+	pub fn collect_keys(&self) -> Option<u32> {
+		let walks = self.walks();
+		let all: Keys = self.keys.iter().map(|&(key, _)| 1 << key).sum();
+		let places: HashMap<u8, usize> = (self.entrances.len()..)
+			.zip(&self.keys)
+			.map(|(place, &(key, _))| (key, place))
+			.collect();
+
+		let start: State = ((0..self.entrances.len()).collect(), 0);
+		let mut best = HashMap::from([(start.clone(), 0)]);
+		let mut queue = BinaryHeap::from([Reverse((0, start))]);
+
+		while let Some(Reverse((steps, (robots, keys)))) = queue.pop() {
+			if keys == all {
+				return Some(steps);
+			}
+
+			if best
+				.get(&(robots.clone(), keys))
+				.is_some_and(|&known| known < steps)
+			{
+				continue;
+			}
+
+			for (robot, &at) in robots.iter().enumerate() {
+				for walk in &walks[at] {
+					if keys & (1 << walk.key) != 0 || walk.doors & !keys != 0 {
+						continue;
+					}
+
+					let mut robots = robots.clone();
+					robots[robot] = places[&walk.key];
+
+					let next = (robots, keys | walk.keys | (1 << walk.key));
+					let steps = steps + walk.steps;
+
+					if best.get(&next).map_or(true, |&known| steps < known) {
+						best.insert(next.clone(), steps);
+						queue.push(Reverse((steps, next)));
+					}
+				}
+			}
+		}
+
+		None
+	}
+}
+
+pub type Intermediate = Vault;
 pub type Output = u32;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	let cells = daocutil::parse::lines(input, |line| {
+		line
+			.char_indices()
+			.map(|(x, c)| {
+				Cell::from_char(c).ok_or_else(|| {
+					ParseError::invalid(x + 1, &c.to_string(), "expected `#`, `.`, `@` or a letter")
+				})
+			})
+			.collect::<Result<Vec<_>, _>>()
+	})?;
+
+	Ok(Vault::new(cells)?)
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(vault: &Intermediate) -> Option<Output> {
+	vault.collect_keys()
 }
 
+daocutil::generate_example_tests!(
+	parse, part_one,
+	part_one_example_1 | include_str!("examples/day18-1") => Some(8),
+	part_one_example_2 | include_str!("examples/day18-2") => Some(86),
+	part_one_example_3 | include_str!("examples/day18-3") => Some(132),
+	part_one_example_4 | include_str!("examples/day18-4") => Some(136),
+	part_one_example_5 | include_str!("examples/day18-5") => Some(81),
+);
+
+/// Splits the vault into four first, unless it already has been.
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(vault: &Intermediate) -> Option<Output> {
+	match vault.entrances.len() {
+		1 => vault.split()?.collect_keys(),
+		_ => vault.collect_keys(),
+	}
+}
+
+daocutil::generate_example_tests!(
+	parse, part_two,
+	part_two_example_6 | include_str!("examples/day18-6") => Some(8),
+	part_two_example_7 | include_str!("examples/day18-7") => Some(24),
+	part_two_example_8 | include_str!("examples/day18-8") => Some(32),
+	part_two_example_9 | include_str!("examples/day18-9") => Some(72),
+);
+
+#[test]
+fn split_vault() {
+	let vault = parse(include_str!("examples/day18-6"))
+		.unwrap()
+		.split()
+		.unwrap();
+
+	assert_eq!(vault.entrances, [(2, 2), (4, 2), (2, 4), (4, 4)]);
+	assert_eq!(vault.cell((3, 3)), Cell::Wall);
+	assert_eq!(vault.cell((3, 2)), Cell::Wall);
+	assert_eq!(vault.cell((2, 3)), Cell::Wall);
+	assert!(vault.split().is_none());
+}
+
+#[test]
+fn malformed_vaults() {
+	assert_eq!(
+		parse("#a.b#").unwrap_err().to_string(),
+		"the vault has no entrance"
+	);
+	assert_eq!(
+		parse("#a@a#").unwrap_err().to_string(),
+		"there is more than one key `a`"
+	);
+	assert_eq!(
+		parse("#@.?#")
+			.unwrap_err()
+			.downcast::<ParseError>()
+			.unwrap()
+			.column,
+		4
+	);
 }
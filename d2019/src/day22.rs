@@ -1,17 +1,310 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use core::{num::NonZeroU64, str::FromStr};
+
+use daocutil::parse::ParseError;
+use num_integer::Integer;
+
+const SMALL_DECK: u64 = 10_007;
+const CARD: u64 = 2019;
+
+const HUGE_DECK: u64 = 119_315_717_514_047;
+const REPEATS: u64 = 101_741_582_076_661;
+const POSITION: u64 = 2020;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Technique {
+	NewStack,
+	Cut(i64),
+	Increment(i64),
+}
+
+impl FromStr for Technique {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, ParseError> {
+		if s == "deal into new stack" {
+			Ok(Self::NewStack)
+		} else if s.starts_with("cut ") {
+			daocutil::scan!(s, "cut {}", i64).map(|(cards,)| Self::Cut(cards))
+		} else if s.starts_with("deal with increment ") {
+			daocutil::scan!(s, "deal with increment {}", i64).map(|(step,)| Self::Increment(step))
+		} else {
+			Err(ParseError::invalid(1, s, "unknown shuffle technique"))
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShuffleError {
+	#[error(
+		"dealing with increment {increment} would put two cards of a {size}-card deck in one place"
+	)]
+	Increment { increment: i64, size: NonZeroU64 },
+}
+
+/// A shuffle of a deck of `size` cards, as the position `a * p + b` (mod `size`) that the card at
+/// position `p` ends up in.  Every technique moves cards like this, and so does doing one shuffle
+/// after another, so any number of techniques (repeated any number of times) boil down to just
+/// two numbers.
+///
+/// Positions stay below `size`, so they fit in a `u64` and the product of two (plus another
+/// position) fits in a `u128`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shuffle {
+	a: u128,
+	b: u128,
+	size: NonZeroU64,
+}
+
+impl Shuffle {
+	/// The shuffle which leaves every card where it is.
+	pub fn identity(size: NonZeroU64) -> Self {
+		Self {
+			a: 1 % u128::from(size.get()),
+			b: 0,
+			size,
+		}
+	}
+
+	/// # Errors
+	///
+	/// Fails for an increment which shares a factor with `size`, since that would deal two cards
+	/// into the same place.
+	pub fn technique(technique: Technique, size: NonZeroU64) -> Result<Self, ShuffleError> {
+		let modulus = i128::from(size.get());
+
+		let (a, b) = match technique {
+			Technique::NewStack => (-1, -1),
+			Technique::Cut(cards) => (1, -i128::from(cards)),
+			Technique::Increment(increment) => {
+				if i128::from(increment).gcd(&modulus) != 1 {
+					return Err(ShuffleError::Increment { increment, size });
+				}
+
+				(i128::from(increment), 0)
+			}
+		};
+
+		// Both are in `0..size` once wrapped, so they can't be negative.
+		Ok(Self {
+			a: a.rem_euclid(modulus).unsigned_abs(),
+			b: b.rem_euclid(modulus).unsigned_abs(),
+			size,
+		})
+	}
+
+	/// Every technique in turn.
+	///
+	/// # Errors
+	///
+	/// See [`Shuffle::technique`].
+	pub fn techniques(techniques: &[Technique], size: NonZeroU64) -> Result<Self, ShuffleError> {
+		techniques
+			.iter()
+			.try_fold(Self::identity(size), |shuffle, &technique| {
+				Ok(shuffle.then(Self::technique(technique, size)?))
+			})
+	}
+
+	fn modulus(self) -> u128 {
+		u128::from(self.size.get())
+	}
+
+	/// This shuffle followed by `next`.
+	#[must_use]
+	pub fn then(self, next: Self) -> Self {
+		Self {
+			a: (next.a * self.a) % self.modulus(),
+			b: (next.a * self.b + next.b) % self.modulus(),
+			size: self.size,
+		}
+	}
+
+	/// This shuffle done `times` times over, by repeated squaring.
+	#[must_use]
+	pub fn repeat(self, mut times: u64) -> Self {
+		let mut result = Self::identity(self.size);
+		let mut square = self;
+
+		while times > 0 {
+			if times & 1 == 1 {
+				result = result.then(square);
+			}
+
+			square = square.then(square);
+			times >>= 1;
+		}
+
+		result
+	}
+
+	/// The shuffle which puts every card back, or `None` if some cards share a place.
+	#[must_use]
+	pub fn inverse(self) -> Option<Self> {
+		// Both fit in 64 bits, so neither they nor their Bézout coefficients overflow an `i128`.
+		let modulus = i128::from(self.size.get());
+		let gcd = i128::try_from(self.a).ok()?.extended_gcd(&modulus);
+
+		if gcd.gcd != 1 {
+			return None;
+		}
+
+		let a = gcd.x.rem_euclid(modulus).unsigned_abs();
+
+		Some(Self {
+			a,
+			b: (self.modulus() - a * self.b % self.modulus()) % self.modulus(),
+			size: self.size,
+		})
+	}
+
+	/// Where the card at `position` ends up.
+	pub fn apply(self, position: u64) -> u64 {
+		let position = u128::from(position) % self.modulus();
+
+		// Below `size`, which is a `u64`.
+		u64::try_from((self.a * position + self.b) % self.modulus()).unwrap()
+	}
+}
+
+pub type Intermediate = Vec<Technique>;
+pub type Output = u64;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	Ok(daocutil::parse::lines(input, str::parse)?)
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(techniques: &Intermediate) -> Option<Output> {
+	let shuffle = Shuffle::techniques(techniques, NonZeroU64::new(SMALL_DECK)?).ok()?;
+
+	Some(shuffle.apply(CARD))
 }
 
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(techniques: &Intermediate) -> Option<Output> {
+	let shuffle = Shuffle::techniques(techniques, NonZeroU64::new(HUGE_DECK)?)
+		.ok()?
+		.repeat(REPEATS);
+
+	Some(shuffle.inverse()?.apply(POSITION))
+}
+
+#[cfg(test)]
+fn deck(size: u64) -> NonZeroU64 {
+	NonZeroU64::new(size).unwrap()
+}
+
+/// The cards of the shuffled deck, from top to bottom.
+#[cfg(test)]
+fn deal(shuffle: Shuffle) -> Vec<u64> {
+	let inverse = shuffle.inverse().unwrap();
+
+	(0..shuffle.size.get())
+		.map(|position| inverse.apply(position))
+		.collect()
+}
+
+#[test]
+fn examples() {
+	let examples = [
+		(
+			include_str!("examples/day22-1"),
+			[0, 3, 6, 9, 2, 5, 8, 1, 4, 7],
+		),
+		(
+			include_str!("examples/day22-2"),
+			[3, 0, 7, 4, 1, 8, 5, 2, 9, 6],
+		),
+		(
+			include_str!("examples/day22-3"),
+			[6, 3, 0, 7, 4, 1, 8, 5, 2, 9],
+		),
+		(
+			include_str!("examples/day22-4"),
+			[9, 2, 5, 8, 1, 4, 7, 0, 3, 6],
+		),
+	];
+
+	for (input, cards) in examples {
+		let shuffle = Shuffle::techniques(&parse(input).unwrap(), deck(10)).unwrap();
+
+		assert_eq!(deal(shuffle), cards);
+	}
+}
+
+#[test]
+fn single_techniques() {
+	let deal_one = |technique| deal(Shuffle::technique(technique, deck(10)).unwrap());
+
+	assert_eq!(
+		deal_one(Technique::NewStack),
+		[9, 8, 7, 6, 5, 4, 3, 2, 1, 0]
+	);
+	assert_eq!(deal_one(Technique::Cut(3)), [3, 4, 5, 6, 7, 8, 9, 0, 1, 2]);
+	assert_eq!(deal_one(Technique::Cut(-4)), [6, 7, 8, 9, 0, 1, 2, 3, 4, 5]);
+	assert_eq!(
+		deal_one(Technique::Increment(3)),
+		[0, 7, 4, 1, 8, 5, 2, 9, 6, 3]
+	);
+	assert_eq!(
+		Shuffle::technique(Technique::Increment(4), deck(10)),
+		Err(ShuffleError::Increment {
+			increment: 4,
+			size: deck(10)
+		})
+	);
+}
+
+#[test]
+fn repeats_match_doing_it_by_hand() {
+	let shuffle =
+		Shuffle::techniques(&parse(include_str!("examples/day22-4")).unwrap(), deck(11)).unwrap();
+	let by_hand = (0..13).fold(Shuffle::identity(deck(11)), |done, _| done.then(shuffle));
+
+	assert_eq!(shuffle.repeat(13), by_hand);
+	assert_eq!(
+		shuffle
+			.repeat(13)
+			.then(shuffle.repeat(13).inverse().unwrap()),
+		Shuffle::identity(deck(11))
+	);
+}
+
+#[test]
+fn largest_deck() {
+	let size = deck(u64::MAX);
+	let shuffle = Shuffle::techniques(
+		&[
+			Technique::NewStack,
+			Technique::Increment(2),
+			Technique::Cut(-3),
+		],
+		size,
+	)
+	.unwrap();
+
+	// The top card goes to the bottom, doubling wraps it round to just above that, and cutting
+	// moves it past the end again.
+	assert_eq!(shuffle.apply(0), 1);
+	assert_eq!(shuffle.apply(u64::MAX - 1), 3);
+	assert_eq!(
+		shuffle
+			.repeat(REPEATS)
+			.then(shuffle.repeat(REPEATS).inverse().unwrap()),
+		Shuffle::identity(size)
+	);
+	assert_eq!(Shuffle::identity(deck(1)).apply(5), 0);
+}
+
+#[test]
+fn unknown_technique() {
+	assert_eq!(
+		parse("cut 3\nshuffle thoroughly")
+			.unwrap_err()
+			.downcast::<ParseError>()
+			.unwrap()
+			.line,
+		Some(2)
+	);
 }
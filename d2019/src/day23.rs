@@ -1,17 +1,44 @@
-pub type Intermediate = ();
-pub type Output = u32;
+use crate::intcode::{
+	network::{Network, Outcome, PacketNetwork},
+	Intcode, Program,
+};
+
+pub type Intermediate = Program;
+pub type Output = i64;
+
+const COMPUTERS: i64 = 50;
 
 /// # Errors
-pub fn parse(_data: &str) -> anyhow::Result<Intermediate> {
-	Ok(())
+pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
+	Ok(input.parse()?)
+}
+
+/// Boots every computer with its network address and runs them until `topology` stops them.
+fn boot(program: &Intermediate, topology: PacketNetwork) -> Option<PacketNetwork> {
+	let computers = (0..COMPUTERS)
+		.map(|address| {
+			let mut computer = Intcode::from(program);
+			computer.input(address);
+			computer
+		})
+		.collect();
+
+	let mut network = Network::new(computers, topology);
+
+	match network.run() {
+		Ok(Outcome::Stopped) => Some(network.topology().clone()),
+		_ => None,
+	}
 }
 
 #[must_use]
-pub fn part_one(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_one(program: &Intermediate) -> Option<Output> {
+	let (_, y) = boot(program, PacketNetwork::new())?.first_to_nat()?;
+
+	Some(y)
 }
 
 #[must_use]
-pub fn part_two(_intermediate: &Intermediate) -> Option<Output> {
-	None
+pub fn part_two(program: &Intermediate) -> Option<Output> {
+	boot(program, PacketNetwork::with_nat())?.repeated()
 }
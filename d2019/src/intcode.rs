@@ -1,6 +1,58 @@
-use std::collections::VecDeque;
+use std::{
+	collections::VecDeque,
+	io::{self, BufRead, Write},
+};
+
+pub mod arcade;
+pub mod ascii;
+pub mod asm;
+pub mod debug;
+pub mod disasm;
+pub mod droid;
+pub mod memory;
+pub mod network;
+pub mod program;
+pub mod rewind;
+pub mod robot;
+
+use memory::Memory;
+pub use program::{Program, ProgramError};
+
+/// Something that went wrong while executing a program.  The machine is left pointing at the
+/// offending instruction, so stepping it again reports the same fault.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+	#[error("unknown opcode {0}")]
+	UnknownOpcode(i64),
+	#[error("unknown parameter mode {0}")]
+	UnknownParameterMode(i64),
+	#[error("immediate parameter used as an address")]
+	ImmediateAddress,
+	#[error("attempted to access negative address {0}")]
+	NegativeAddress(i64),
+	#[error("attempted to write to address {0}, past the end of memory")]
+	AddressTooLarge(usize),
+	#[error("arithmetic overflow")]
+	Overflow,
+	#[error("program wanted input, but none was available")]
+	InputExhausted,
+}
+
+/// Where a machine stopped after being stepped or run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+	/// An instruction was executed and there is more to do.  Only returned by [`Intcode::step`].
+	Running,
+	/// The program wants input which hasn't been queued yet.  Queue some with [`Intcode::input`]
+	/// and carry on.
+	NeedsInput,
+	/// The program produced a value.
+	Output(i64),
+	Halted,
+	Fault(Fault),
+}
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Opcode {
 	Add,
 	Mul,
@@ -10,357 +62,480 @@ pub enum Opcode {
 	JumpIfFalse,
 	LessThan,
 	Equals,
+	AdjustRelativeBase,
 	Halt,
 }
 
-impl From<i32> for Opcode {
-	fn from(raw: i32) -> Opcode {
-		use Opcode::{Add, Equals, Halt, Input, JumpIfFalse, JumpIfTrue, LessThan, Mul, Output};
+impl Opcode {
+	/// The number of parameters the instruction takes.
+	pub fn parameter_count(self) -> usize {
+		match self {
+			Self::Add | Self::Mul | Self::LessThan | Self::Equals => 3,
+			Self::JumpIfTrue | Self::JumpIfFalse => 2,
+			Self::Input | Self::Output | Self::AdjustRelativeBase => 1,
+			Self::Halt => 0,
+		}
+	}
+
+	/// Which parameter, if any, is an address the instruction writes to.
+	pub fn written_parameter(self) -> Option<usize> {
+		match self {
+			Self::Add | Self::Mul | Self::LessThan | Self::Equals => Some(2),
+			Self::Input => Some(0),
+			_ => None,
+		}
+	}
+
+	/// The opcode's numeric value, without any parameter modes.
+	pub fn code(self) -> i64 {
+		match self {
+			Self::Add => 1,
+			Self::Mul => 2,
+			Self::Input => 3,
+			Self::Output => 4,
+			Self::JumpIfTrue => 5,
+			Self::JumpIfFalse => 6,
+			Self::LessThan => 7,
+			Self::Equals => 8,
+			Self::AdjustRelativeBase => 9,
+			Self::Halt => 99,
+		}
+	}
+
+	pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+		[
+			Self::Add,
+			Self::Mul,
+			Self::Input,
+			Self::Output,
+			Self::JumpIfTrue,
+			Self::JumpIfFalse,
+			Self::LessThan,
+			Self::Equals,
+			Self::AdjustRelativeBase,
+			Self::Halt,
+		]
+		.into_iter()
+		.find(|opcode| opcode.mnemonic() == mnemonic)
+	}
+
+	pub fn mnemonic(self) -> &'static str {
+		match self {
+			Self::Add => "add",
+			Self::Mul => "mul",
+			Self::Input => "in",
+			Self::Output => "out",
+			Self::JumpIfTrue => "jnz",
+			Self::JumpIfFalse => "jz",
+			Self::LessThan => "lt",
+			Self::Equals => "eq",
+			Self::AdjustRelativeBase => "arb",
+			Self::Halt => "hlt",
+		}
+	}
+}
+
+impl TryFrom<i64> for Opcode {
+	type Error = Fault;
 
-		// We should not get values >= 100.
-		assert_eq!(raw % 100, raw);
+	fn try_from(raw: i64) -> Result<Opcode, Fault> {
+		use Opcode::{
+			Add, AdjustRelativeBase, Equals, Halt, Input, JumpIfFalse, JumpIfTrue, LessThan, Mul, Output,
+		};
 
 		match raw {
-			1 => Add,
-			2 => Mul,
-			3 => Input,
-			4 => Output,
-			5 => JumpIfTrue,
-			6 => JumpIfFalse,
-			7 => LessThan,
-			8 => Equals,
-			99 => Halt,
-			_ => panic!("unknown opcode {raw}"),
+			1 => Ok(Add),
+			2 => Ok(Mul),
+			3 => Ok(Input),
+			4 => Ok(Output),
+			5 => Ok(JumpIfTrue),
+			6 => Ok(JumpIfFalse),
+			7 => Ok(LessThan),
+			8 => Ok(Equals),
+			9 => Ok(AdjustRelativeBase),
+			99 => Ok(Halt),
+			_ => Err(Fault::UnknownOpcode(raw)),
 		}
 	}
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ParameterMode {
 	Position,
 	Immediate,
+	Relative,
 }
 
-impl From<i32> for ParameterMode {
-	fn from(raw: i32) -> ParameterMode {
+impl ParameterMode {
+	pub fn code(self) -> i64 {
+		match self {
+			Self::Position => 0,
+			Self::Immediate => 1,
+			Self::Relative => 2,
+		}
+	}
+}
+
+impl TryFrom<i64> for ParameterMode {
+	type Error = Fault;
+
+	fn try_from(raw: i64) -> Result<ParameterMode, Fault> {
 		match raw {
-			0 => Self::Position,
-			1 => Self::Immediate,
-			_ => unimplemented!(),
+			0 => Ok(Self::Position),
+			1 => Ok(Self::Immediate),
+			2 => Ok(Self::Relative),
+			_ => Err(Fault::UnknownParameterMode(raw)),
 		}
 	}
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Instruction {
 	opcode: Opcode,
 	parameter_modes: (ParameterMode, ParameterMode, ParameterMode),
 }
 
-impl From<i32> for Instruction {
-	fn from(raw: i32) -> Instruction {
-		let opcode = Opcode::from(raw % 100);
-		let mode_0: ParameterMode = ((raw / 100) % 10).into();
-		let mode_1: ParameterMode = ((raw / 1000) % 10).into();
-		let mode_2: ParameterMode = ((raw / 10000) % 10).into();
+impl TryFrom<i64> for Instruction {
+	type Error = Fault;
+
+	fn try_from(raw: i64) -> Result<Instruction, Fault> {
+		if raw < 0 {
+			return Err(Fault::UnknownOpcode(raw));
+		}
+
+		let opcode = Opcode::try_from(raw % 100)?;
+		let mode_0 = ParameterMode::try_from((raw / 100) % 10)?;
+		let mode_1 = ParameterMode::try_from((raw / 1000) % 10)?;
+		let mode_2 = ParameterMode::try_from((raw / 10000) % 10)?;
 		let parameter_modes = (mode_0, mode_1, mode_2);
 
-		Instruction {
+		Ok(Instruction {
 			opcode,
 			parameter_modes,
-		}
+		})
 	}
 }
 
 #[test]
 fn instruction_example() {
 	assert_eq!(
-		Instruction {
+		Ok(Instruction {
 			opcode: Opcode::Mul,
 			parameter_modes: (
 				ParameterMode::Position,
 				ParameterMode::Immediate,
 				ParameterMode::Position
 			)
-		},
-		1002_i32.into()
+		}),
+		Instruction::try_from(1002_i64)
 	);
 }
 
-#[derive(Debug)]
+/// A machine, along with the program it's running.  Memory is copy-on-write, so cloning a machine
+/// to explore several futures from the same state is cheap.
+#[derive(Debug, Clone)]
 pub struct Intcode {
-	inner: Vec<i32>,
+	inner: Memory,
 	head: usize,
-	interactive: bool,
-	input: VecDeque<i32>,
-	output: VecDeque<i32>,
-	did_halt: bool,
+	relative_base: i64,
+	input: VecDeque<i64>,
 }
 
 impl Intcode {
-	pub fn new(inner: Vec<i32>, head: usize) -> Self {
-		let (input, output) = (VecDeque::new(), VecDeque::new());
-
+	pub fn new(inner: Vec<i64>, head: usize) -> Self {
 		Self {
-			inner,
+			inner: inner.into(),
 			head,
-			interactive: true,
-			input,
-			output,
-			did_halt: false,
+			relative_base: 0,
+			input: VecDeque::new(),
 		}
 	}
 
-	pub fn from_data(inner: Vec<i32>) -> Self {
+	pub fn from_data(inner: Vec<i64>) -> Self {
 		Self::new(inner, 0_usize)
 	}
 
-	pub fn input(&mut self, value: i32) {
+	/// Queues a value for the program to read.
+	pub fn input(&mut self, value: i64) {
 		self.input.push_back(value);
-
-		if self.interactive {
-			self.interactive = false;
-		}
 	}
 
-	pub fn output(&mut self) -> Option<i32> {
-		self.output.pop_front()
+	/// The number of values queued for the program to read.
+	pub fn pending_input(&self) -> usize {
+		self.input.len()
 	}
 
-	fn resolve_parameter(&self, value: i32, mode: &ParameterMode) -> i32 {
-		match mode {
-			ParameterMode::Position => self.inner[usize::try_from(value).expect("what")],
-			ParameterMode::Immediate => value,
-		}
+	/// The values queued for the program to read, in the order it will read them.
+	pub fn input_queue(&self) -> &VecDeque<i64> {
+		&self.input
 	}
 
-	pub fn did_halt(&self) -> bool {
-		self.did_halt
+	/// The address of the next instruction to execute.
+	pub fn head(&self) -> usize {
+		self.head
 	}
 
-	fn eval_add(&mut self, parameter_modes: &(ParameterMode, ParameterMode, ParameterMode)) {
-		let param_a = self.inner[self.head + 1];
-		let param_b = self.inner[self.head + 2];
-		let param_outpos = self.inner[self.head + 3];
-
-		// Ensure no illegal output parameter in immediate mode.
-		assert!(parameter_modes.2 != ParameterMode::Immediate);
-
-		let a = self.resolve_parameter(param_a, &parameter_modes.0);
-		let b = self.resolve_parameter(param_b, &parameter_modes.1);
-
-		self.inner[usize::try_from(param_outpos)
-			.expect("failed to convert output position for add instruction")] = a + b;
-
-		self.head += 4;
+	pub fn relative_base(&self) -> i64 {
+		self.relative_base
 	}
 
-	fn eval_mul(&mut self, parameter_modes: &(ParameterMode, ParameterMode, ParameterMode)) {
-		let param_a = self.inner[self.head + 1];
-		let param_b = self.inner[self.head + 2];
-		let param_outpos = self.inner[self.head + 3];
-
-		// Ensure no illegal output parameter in immediate mode.
-		assert!(parameter_modes.2 != ParameterMode::Immediate);
-
-		let a = self.resolve_parameter(param_a, &parameter_modes.0);
-		let b = self.resolve_parameter(param_b, &parameter_modes.1);
-
-		self.inner[usize::try_from(param_outpos)
-			.expect("failed to convert output position for mul instruction")] = a * b;
-
-		self.head += 4;
+	/// Reads the value at `address`.  Memory past the end of the program reads as zero.
+	pub fn read(&self, address: usize) -> i64 {
+		self.inner.read(address)
 	}
 
-	fn eval_input(&mut self) {
-		let location = self.inner[self.head + 1];
-
-		// If the user has supplied us with an input in the queue, use it.
-		// Otherwise, prompt for an input.
-		if self.interactive {
-			use std::io::{stdin, stdout, Write};
-
-			let mut input: String = String::new();
-			print!("<= ");
-			let _ = stdout().flush();
-
-			stdin().read_line(&mut input).expect("invalid input");
-			let input: i32 = input.trim_end().parse::<i32>().expect("invalid input");
-			self.inner[usize::try_from(location).unwrap()] = input;
-		} else if let Some(input) = self.input.pop_front() {
-			self.inner[usize::try_from(location).unwrap()] = input;
-		} else {
-			panic!("Attempted to take input in non-interactive mode without anything in input buffer");
-		}
-
-		self.head += 2;
+	/// Writes `value` to `address`, growing memory to fit it if needed.
+	///
+	/// # Errors
+	///
+	/// Fails if `address` is past [`memory::MEMORY_LIMIT`].
+	pub fn write(&mut self, address: usize, value: i64) -> Result<(), Fault> {
+		self.inner.write(address, value)
 	}
 
-	fn eval_output(&mut self, parameter_modes: &(ParameterMode, ParameterMode, ParameterMode)) {
-		let param = self.inner[self.head + 1];
-
-		let value = self.resolve_parameter(param, &parameter_modes.0);
-
-		if self.interactive {
-			println!("=> {value}");
-		} else {
-			self.output.push_back(value);
-		}
-
-		self.head += 2;
+	/// The raw value of the `n`th parameter of the current instruction.
+	fn parameter(&self, n: usize) -> i64 {
+		self.read(self.head + n)
 	}
 
-	fn eval_jump_if_true(&mut self, parameter_modes: &(ParameterMode, ParameterMode, ParameterMode)) {
-		let param_a = self.inner[self.head + 1];
-		let param_b = self.inner[self.head + 2];
-
-		let a = self.resolve_parameter(param_a, &parameter_modes.0);
-		let b = self.resolve_parameter(param_b, &parameter_modes.1);
-
-		if a != 0 {
-			self.head = usize::try_from(b).unwrap();
-		} else {
-			self.head += 3;
-		}
+	/// Resolves a parameter which refers to a location in memory.
+	fn resolve_address(&self, value: i64, mode: ParameterMode) -> Result<usize, Fault> {
+		let address = match mode {
+			ParameterMode::Position => value,
+			ParameterMode::Relative => self
+				.relative_base
+				.checked_add(value)
+				.ok_or(Fault::Overflow)?,
+			ParameterMode::Immediate => return Err(Fault::ImmediateAddress),
+		};
+
+		usize::try_from(address).map_err(|_| Fault::NegativeAddress(address))
 	}
 
-	fn eval_jump_if_false(
-		&mut self,
-		parameter_modes: &(ParameterMode, ParameterMode, ParameterMode),
-	) {
-		let param_a = self.inner[self.head + 1];
-		let param_b = self.inner[self.head + 2];
-
-		let a = self.resolve_parameter(param_a, &parameter_modes.0);
-		let b = self.resolve_parameter(param_b, &parameter_modes.1);
-
-		if a == 0 {
-			self.head = usize::try_from(b).unwrap();
-		} else {
-			self.head += 3;
+	fn resolve_parameter(&self, value: i64, mode: ParameterMode) -> Result<i64, Fault> {
+		match mode {
+			ParameterMode::Immediate => Ok(value),
+			ParameterMode::Position | ParameterMode::Relative => {
+				Ok(self.read(self.resolve_address(value, mode)?))
+			}
 		}
 	}
 
-	fn eval_less_than(&mut self, parameter_modes: &(ParameterMode, ParameterMode, ParameterMode)) {
-		let param_a = self.inner[self.head + 1];
-		let param_b = self.inner[self.head + 2];
-		let param_outpos = self.inner[self.head + 3];
-
-		let a = self.resolve_parameter(param_a, &parameter_modes.0);
-		let b = self.resolve_parameter(param_b, &parameter_modes.1);
-
-		self.inner[usize::try_from(param_outpos).unwrap()] = (a < b).into();
-		self.head += 4;
+	/// Resolves the first two parameters as values and the third as an address, as used by the
+	/// arithmetic and comparison instructions.
+	fn resolve_binary(
+		&self,
+		parameter_modes: (ParameterMode, ParameterMode, ParameterMode),
+	) -> Result<(i64, i64, usize), Fault> {
+		let a = self.resolve_parameter(self.parameter(1), parameter_modes.0)?;
+		let b = self.resolve_parameter(self.parameter(2), parameter_modes.1)?;
+		let outpos = self.resolve_address(self.parameter(3), parameter_modes.2)?;
+
+		Ok((a, b, outpos))
 	}
 
-	fn eval_equals(&mut self, parameter_modes: &(ParameterMode, ParameterMode, ParameterMode)) {
-		let param_a = self.inner[self.head + 1];
-		let param_b = self.inner[self.head + 2];
-		let param_outpos = self.inner[self.head + 3];
-
-		let a = self.resolve_parameter(param_a, &parameter_modes.0);
-		let b = self.resolve_parameter(param_b, &parameter_modes.1);
-
-		self.inner[usize::try_from(param_outpos).unwrap()] = (a == b).into();
-		self.head += 4;
+	/// Executes a single instruction.
+	pub fn step(&mut self) -> State {
+		match self.try_step() {
+			Ok(state) => state,
+			Err(fault) => State::Fault(fault),
+		}
 	}
 
-	pub fn step(&mut self) -> Option<()> {
-		// Fetch
-		let instruction: Instruction = Instruction::from(self.inner[self.head]);
-		let opcode: Opcode = instruction.opcode;
-		let parameter_modes: (ParameterMode, ParameterMode, ParameterMode) =
-			instruction.parameter_modes;
+	fn try_step(&mut self) -> Result<State, Fault> {
+		// Fetch, from the cache unless the instruction has been written over since it was last run.
+		let instruction = self.inner.decode(self.head)?;
+		let modes = instruction.parameter_modes;
 
-		match opcode {
+		match instruction.opcode {
 			Opcode::Add => {
-				self.eval_add(&parameter_modes);
-				Some(())
+				let (a, b, outpos) = self.resolve_binary(modes)?;
+				self.write(outpos, a.checked_add(b).ok_or(Fault::Overflow)?)?;
+				self.head += 4;
 			}
 
 			Opcode::Mul => {
-				self.eval_mul(&parameter_modes);
-				Some(())
+				let (a, b, outpos) = self.resolve_binary(modes)?;
+				self.write(outpos, a.checked_mul(b).ok_or(Fault::Overflow)?)?;
+				self.head += 4;
 			}
 
 			Opcode::Input => {
-				self.eval_input();
-				Some(())
+				let location = self.resolve_address(self.parameter(1), modes.0)?;
+
+				// Leave the head where it is, so that the instruction is retried once the caller
+				// has queued something.
+				let Some(&value) = self.input.front() else {
+					return Ok(State::NeedsInput);
+				};
+
+				// Only take the value once it's written, so a faulting write leaves it queued.
+				self.write(location, value)?;
+				self.input.pop_front();
+				self.head += 2;
 			}
 
 			Opcode::Output => {
-				self.eval_output(&parameter_modes);
-				Some(())
-			}
+				let value = self.resolve_parameter(self.parameter(1), modes.0)?;
+				self.head += 2;
 
-			Opcode::JumpIfTrue => {
-				self.eval_jump_if_true(&parameter_modes);
-				Some(())
+				return Ok(State::Output(value));
 			}
 
-			Opcode::JumpIfFalse => {
-				self.eval_jump_if_false(&parameter_modes);
-				Some(())
+			Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+				let a = self.resolve_parameter(self.parameter(1), modes.0)?;
+				let b = self.resolve_parameter(self.parameter(2), modes.1)?;
+
+				if (a != 0) == (instruction.opcode == Opcode::JumpIfTrue) {
+					self.head = usize::try_from(b).map_err(|_| Fault::NegativeAddress(b))?;
+				} else {
+					self.head += 3;
+				}
 			}
 
 			Opcode::LessThan => {
-				self.eval_less_than(&parameter_modes);
-				Some(())
+				let (a, b, outpos) = self.resolve_binary(modes)?;
+				self.write(outpos, (a < b).into())?;
+				self.head += 4;
 			}
 
 			Opcode::Equals => {
-				self.eval_equals(&parameter_modes);
-				Some(())
+				let (a, b, outpos) = self.resolve_binary(modes)?;
+				self.write(outpos, (a == b).into())?;
+				self.head += 4;
 			}
 
-			Opcode::Halt => {
-				self.did_halt = true;
-				None
+			Opcode::AdjustRelativeBase => {
+				let offset = self.resolve_parameter(self.parameter(1), modes.0)?;
+				self.relative_base = self
+					.relative_base
+					.checked_add(offset)
+					.ok_or(Fault::Overflow)?;
+				self.head += 2;
 			}
+
+			Opcode::Halt => return Ok(State::Halted),
 		}
+
+		Ok(State::Running)
 	}
 
-	#[must_use]
-	pub fn run(mut self) -> Self {
+	/// Runs until the program outputs something, needs input, halts or faults.
+	pub fn run(&mut self) -> State {
 		loop {
-			if self.step().is_none() {
-				break self;
+			let state = self.step();
+
+			if state != State::Running {
+				break state;
 			}
 		}
 	}
 
-	#[must_use]
-	pub fn run_til_next_output(&mut self) -> &mut Self {
-		loop {
-			if self.step().is_none() {
-				break self;
-			}
-
-			if !self.output.is_empty() {
-				break self;
-			}
+	/// Runs the program to completion on the input queued so far, collecting its output.
+	///
+	/// # Errors
+	///
+	/// Fails if the program faults, or wants more input than was queued.
+	pub fn run_to_halt(&mut self) -> Result<Vec<i64>, Fault> {
+		let mut outputs = Vec::new();
 
-			if self.did_halt {
-				println!("Did halt... before output was produced!");
-				break self;
+		loop {
+			match self.run() {
+				State::Output(value) => outputs.push(value),
+				State::Halted => break Ok(outputs),
+				State::NeedsInput => break Err(Fault::InputExhausted),
+				State::Fault(fault) => break Err(fault),
+				State::Running => unreachable!("run never stops while running"),
 			}
 		}
 	}
 
-	pub fn data(&self) -> &Vec<i32> {
+	pub fn memory(&self) -> &Memory {
 		&self.inner
 	}
+
+	/// A copy of the whole of memory.
+	pub fn data(&self) -> Vec<i64> {
+		self.inner.to_vec()
+	}
 }
 
-impl From<Vec<i32>> for Intcode {
-	fn from(program: Vec<i32>) -> Self {
+impl From<Vec<i64>> for Intcode {
+	fn from(program: Vec<i64>) -> Self {
 		Self::from_data(program)
 	}
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ConsoleError {
+	#[error(transparent)]
+	Fault(#[from] Fault),
+	#[error("failed to talk to the console")]
+	Io(#[from] io::Error),
+	#[error("`{0}` is not a number")]
+	InvalidInput(String),
+	#[error("the console ran out of input")]
+	EndOfInput,
+}
+
+/// Runs a machine interactively, prompting for each input on one line of `input` and printing
+/// each output to `output`.
+#[derive(Debug)]
+pub struct Console<R, W> {
+	input: R,
+	output: W,
+}
+
+impl Console<io::StdinLock<'static>, io::Stdout> {
+	/// A console talking to the terminal.
+	pub fn stdio() -> Self {
+		Self::new(io::stdin().lock(), io::stdout())
+	}
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+	pub fn new(input: R, output: W) -> Self {
+		Self { input, output }
+	}
+
+	/// Runs `machine` until it halts.
+	///
+	/// # Errors
+	///
+	/// Fails if the machine faults, the console can't be read or written, or a line entered isn't
+	/// a number.
+	pub fn run(&mut self, machine: &mut Intcode) -> Result<(), ConsoleError> {
+		loop {
+			match machine.run() {
+				State::NeedsInput => machine.input(self.prompt()?),
+				State::Output(value) => writeln!(self.output, "=> {value}")?,
+				State::Halted => break Ok(()),
+				State::Fault(fault) => break Err(fault.into()),
+				State::Running => unreachable!("run never stops while running"),
+			}
+		}
+	}
+
+	fn prompt(&mut self) -> Result<i64, ConsoleError> {
+		write!(self.output, "<= ")?;
+		self.output.flush()?;
+
+		let mut line = String::new();
+
+		if self.input.read_line(&mut line)? == 0 {
+			return Err(ConsoleError::EndOfInput);
+		}
+
+		let line = line.trim();
+		line
+			.parse()
+			.map_err(|_| ConsoleError::InvalidInput(line.to_string()))
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{Instruction, Intcode};
+	use super::{Console, ConsoleError, Fault, Instruction, Intcode, State};
 
 	#[cfg(test)]
 	mod instruction {
@@ -370,60 +545,60 @@ mod tests {
 		#[test]
 		fn standard_add_correct() {
 			assert_eq!(
-				Instruction::from(1),
-				Instruction {
+				Instruction::try_from(1),
+				Ok(Instruction {
 					opcode: Opcode::Add,
 					parameter_modes: (
 						ParameterMode::Position,
 						ParameterMode::Position,
 						ParameterMode::Position
 					)
-				}
+				})
 			);
 		}
 
 		#[test]
 		fn standard_mul_correct() {
 			assert_eq!(
-				Instruction::from(2),
-				Instruction {
+				Instruction::try_from(2),
+				Ok(Instruction {
 					opcode: Opcode::Mul,
 					parameter_modes: (
 						ParameterMode::Position,
 						ParameterMode::Position,
 						ParameterMode::Position
 					)
-				}
+				})
 			);
 		}
 
 		#[test]
 		fn standard_input_correct() {
 			assert_eq!(
-				Instruction::from(3),
-				Instruction {
+				Instruction::try_from(3),
+				Ok(Instruction {
 					opcode: Opcode::Input,
 					parameter_modes: (
 						ParameterMode::Position,
 						ParameterMode::Position,
 						ParameterMode::Position
 					)
-				}
+				})
 			);
 		}
 
 		#[test]
 		fn standard_output_correct() {
 			assert_eq!(
-				Instruction::from(4),
-				Instruction {
+				Instruction::try_from(4),
+				Ok(Instruction {
 					opcode: Opcode::Output,
 					parameter_modes: (
 						ParameterMode::Position,
 						ParameterMode::Position,
 						ParameterMode::Position
 					)
-				}
+				})
 			);
 		}
 	}
@@ -434,52 +609,50 @@ mod tests {
 
 		// First, we should be able to set the input.
 		program.input(573);
-		assert_eq!(program.data(), &vec![3, 0, 4, 0, 99]);
+		assert_eq!(program.data(), vec![3, 0, 4, 0, 99]);
 
 		// After one step, the value we input should be stored in the data, and we
 		// shouldn't have any output.
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![573, 0, 4, 0, 99]);
-		assert_eq!(program.output(), None);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![573, 0, 4, 0, 99]);
 
 		// Stepping once more, we should now have an output.
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![573, 0, 4, 0, 99]);
-		assert_eq!(program.output(), Some(573));
+		assert_eq!(program.step(), State::Output(573));
+		assert_eq!(program.data(), vec![573, 0, 4, 0, 99]);
 
 		// Stepping once again should halt the program.
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
 	fn pgm_add_immediate() {
 		let mut program: Intcode = Intcode::from(vec![1101, 100, -1, 4, 0]);
 
-		assert_eq!(program.data(), &vec![1101, 100, -1, 4, 0]);
+		assert_eq!(program.data(), vec![1101, 100, -1, 4, 0]);
 
 		// Stepping, opcode 1101 should add the 100 and -1 and store the result, 99,
 		// in position 4.
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![1101, 100, -1, 4, 99]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![1101, 100, -1, 4, 99]);
 
 		// Next step should terminate the program.
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
 	fn pgm_multiply_immediate() {
 		let mut program: Intcode = Intcode::from(vec![1002, 4, 3, 4, 33]);
 
-		assert_eq!(program.data(), &vec![1002, 4, 3, 4, 33]);
+		assert_eq!(program.data(), vec![1002, 4, 3, 4, 33]);
 
 		// Stepping, opcode 1002 should multiply the value at address 4
 		// by the literal value 3 and store it in position 4, so it effectively
 		// multiplies the last position by 3, setting it to the halt instruction.
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![1002, 4, 3, 4, 99]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![1002, 4, 3, 4, 99]);
 
 		// Next step should terminate the program.
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -489,25 +662,24 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
 		program.input(input);
 
-		assert_eq!(program.data(), &vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+		assert_eq!(program.data(), vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
 
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![3, 9, 8, 9, 10, 9, 4, 9, 99, input, 8]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![3, 9, 8, 9, 10, 9, 4, 9, 99, input, 8]);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 9, 8, 9, 10, 9, 4, 9, 99, expected_output, 8]
+			vec![3, 9, 8, 9, 10, 9, 4, 9, 99, expected_output, 8]
 		);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 9, 8, 9, 10, 9, 4, 9, 99, expected_output, 8]
+			vec![3, 9, 8, 9, 10, 9, 4, 9, 99, expected_output, 8]
 		);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -517,25 +689,24 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
 		program.input(input);
 
-		assert_eq!(program.data(), &vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+		assert_eq!(program.data(), vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
 
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![3, 9, 8, 9, 10, 9, 4, 9, 99, input, 8]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![3, 9, 8, 9, 10, 9, 4, 9, 99, input, 8]);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 9, 8, 9, 10, 9, 4, 9, 99, expected_output, 8]
+			vec![3, 9, 8, 9, 10, 9, 4, 9, 99, expected_output, 8]
 		);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 9, 8, 9, 10, 9, 4, 9, 99, expected_output, 8]
+			vec![3, 9, 8, 9, 10, 9, 4, 9, 99, expected_output, 8]
 		);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -545,25 +716,24 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
 		program.input(input);
 
-		assert_eq!(program.data(), &vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
+		assert_eq!(program.data(), vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
 
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![3, 9, 7, 9, 10, 9, 4, 9, 99, input, 8]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![3, 9, 7, 9, 10, 9, 4, 9, 99, input, 8]);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 9, 7, 9, 10, 9, 4, 9, 99, expected_output, 8]
+			vec![3, 9, 7, 9, 10, 9, 4, 9, 99, expected_output, 8]
 		);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 9, 7, 9, 10, 9, 4, 9, 99, expected_output, 8]
+			vec![3, 9, 7, 9, 10, 9, 4, 9, 99, expected_output, 8]
 		);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -573,25 +743,24 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
 		program.input(input);
 
-		assert_eq!(program.data(), &vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
+		assert_eq!(program.data(), vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
 
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![3, 9, 7, 9, 10, 9, 4, 9, 99, input, 8]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![3, 9, 7, 9, 10, 9, 4, 9, 99, input, 8]);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 9, 7, 9, 10, 9, 4, 9, 99, expected_output, 8]
+			vec![3, 9, 7, 9, 10, 9, 4, 9, 99, expected_output, 8]
 		);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 9, 7, 9, 10, 9, 4, 9, 99, expected_output, 8]
+			vec![3, 9, 7, 9, 10, 9, 4, 9, 99, expected_output, 8]
 		);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -601,25 +770,24 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
 		program.input(input);
 
-		assert_eq!(program.data(), &vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
+		assert_eq!(program.data(), vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
 
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![3, 3, 1108, input, 8, 3, 4, 3, 99]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![3, 3, 1108, input, 8, 3, 4, 3, 99]);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1108, expected_output, 8, 3, 4, 3, 99]
+			vec![3, 3, 1108, expected_output, 8, 3, 4, 3, 99]
 		);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1108, expected_output, 8, 3, 4, 3, 99]
+			vec![3, 3, 1108, expected_output, 8, 3, 4, 3, 99]
 		);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -629,25 +797,24 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
 		program.input(input);
 
-		assert_eq!(program.data(), &vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
+		assert_eq!(program.data(), vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
 
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![3, 3, 1108, input, 8, 3, 4, 3, 99]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![3, 3, 1108, input, 8, 3, 4, 3, 99]);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1108, expected_output, 8, 3, 4, 3, 99]
+			vec![3, 3, 1108, expected_output, 8, 3, 4, 3, 99]
 		);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1108, expected_output, 8, 3, 4, 3, 99]
+			vec![3, 3, 1108, expected_output, 8, 3, 4, 3, 99]
 		);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -657,25 +824,24 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
 		program.input(input);
 
-		assert_eq!(program.data(), &vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
+		assert_eq!(program.data(), vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
 
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![3, 3, 1107, input, 8, 3, 4, 3, 99]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![3, 3, 1107, input, 8, 3, 4, 3, 99]);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1107, expected_output, 8, 3, 4, 3, 99]
+			vec![3, 3, 1107, expected_output, 8, 3, 4, 3, 99]
 		);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1107, expected_output, 8, 3, 4, 3, 99]
+			vec![3, 3, 1107, expected_output, 8, 3, 4, 3, 99]
 		);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -685,25 +851,24 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
 		program.input(input);
 
-		assert_eq!(program.data(), &vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
+		assert_eq!(program.data(), vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
 
-		assert_eq!(program.step(), Some(()));
-		assert_eq!(program.data(), &vec![3, 3, 1107, input, 8, 3, 4, 3, 99]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.data(), vec![3, 3, 1107, input, 8, 3, 4, 3, 99]);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1107, expected_output, 8, 3, 4, 3, 99]
+			vec![3, 3, 1107, expected_output, 8, 3, 4, 3, 99]
 		);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1107, expected_output, 8, 3, 4, 3, 99]
+			vec![3, 3, 1107, expected_output, 8, 3, 4, 3, 99]
 		);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -717,33 +882,32 @@ mod tests {
 
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9]
 		);
 		assert_eq!(program.head, 0_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
 		);
 		assert_eq!(program.head, 2_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
 		);
 		assert_eq!(program.head, 9_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
 		);
 		assert_eq!(program.head, 11_usize);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -757,40 +921,39 @@ mod tests {
 
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9]
 		);
 		assert_eq!(program.head, 0_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
 		);
 		assert_eq!(program.head, 2_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 0, 1, 9]
 		);
 		assert_eq!(program.head, 5_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 1, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 1, 1, 9]
 		);
 		assert_eq!(program.head, 9_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 1, 1, 9]
+			vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, input, 1, 1, 9]
 		);
 		assert_eq!(program.head, 11_usize);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -802,40 +965,39 @@ mod tests {
 
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
+			vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
 		);
 		assert_eq!(program.head, 0_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
+			vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
 		);
 		assert_eq!(program.head, 2_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
+			vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
 		);
 		assert_eq!(program.head, 5_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 0]
+			vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 0]
 		);
 		assert_eq!(program.head, 9_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 0]
+			vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 0]
 		);
 		assert_eq!(program.head, 11_usize);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -847,33 +1009,32 @@ mod tests {
 
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
+			vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
 		);
 		assert_eq!(program.head, 0_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
+			vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
 		);
 		assert_eq!(program.head, 2_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
+			vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
 		);
 		assert_eq!(program.head, 9_usize);
 
-		assert_eq!(program.step(), Some(()));
+		assert_eq!(program.step(), State::Output(expected_output));
 		assert_eq!(
 			program.data(),
-			&vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
+			vec![3, 3, 1105, input, 9, 1101, 0, 0, 12, 4, 12, 99, 1]
 		);
 		assert_eq!(program.head, 11_usize);
-		assert_eq!(program.output(), Some(expected_output));
 
-		assert_eq!(program.step(), None);
+		assert_eq!(program.step(), State::Halted);
 	}
 
 	#[test]
@@ -881,41 +1042,196 @@ mod tests {
 		let mut program: Intcode = Intcode::from(vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]);
 		assert_eq!(
 			program.data(),
-			&vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]
+			vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]
 		);
-		program.step();
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![1, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]
+			vec![1, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]
 		);
-		program.step();
+		assert_eq!(program.step(), State::Running);
 		assert_eq!(
 			program.data(),
-			&vec![3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]
+			vec![3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]
 		);
 	}
 
 	#[test]
 	fn pgm_5_a() {
-		let program: Intcode = Intcode::from(vec![1, 0, 0, 0, 99]);
-		assert_eq!(program.run().data(), &vec![2, 0, 0, 0, 99]);
+		let mut program: Intcode = Intcode::from(vec![1, 0, 0, 0, 99]);
+		assert_eq!(program.run(), State::Halted);
+		assert_eq!(program.data(), vec![2, 0, 0, 0, 99]);
 	}
 
 	#[test]
 	fn pgm_5_b() {
-		let program: Intcode = Intcode::from(vec![2, 3, 0, 3, 99]);
-		assert_eq!(program.run().data(), &vec![2, 3, 0, 6, 99]);
+		let mut program: Intcode = Intcode::from(vec![2, 3, 0, 3, 99]);
+		assert_eq!(program.run(), State::Halted);
+		assert_eq!(program.data(), vec![2, 3, 0, 6, 99]);
 	}
 
 	#[test]
 	fn pgm_6() {
-		let program: Intcode = Intcode::from(vec![2, 4, 4, 5, 99, 0]);
-		assert_eq!(program.run().data(), &vec![2, 4, 4, 5, 99, 9801]);
+		let mut program: Intcode = Intcode::from(vec![2, 4, 4, 5, 99, 0]);
+		assert_eq!(program.run(), State::Halted);
+		assert_eq!(program.data(), vec![2, 4, 4, 5, 99, 9801]);
 	}
 
 	#[test]
 	fn pgm_9() {
-		let program: Intcode = Intcode::from(vec![1, 1, 1, 4, 99, 5, 6, 0, 99]);
-		assert_eq!(program.run().data(), &vec![30, 1, 1, 4, 2, 5, 6, 0, 99]);
+		let mut program: Intcode = Intcode::from(vec![1, 1, 1, 4, 99, 5, 6, 0, 99]);
+		assert_eq!(program.run(), State::Halted);
+		assert_eq!(program.data(), vec![30, 1, 1, 4, 2, 5, 6, 0, 99]);
+	}
+
+	#[test]
+	fn relative_mode_instruction() {
+		assert_eq!(
+			Instruction::try_from(21_202),
+			Ok(Instruction {
+				opcode: super::Opcode::Mul,
+				parameter_modes: (
+					super::ParameterMode::Relative,
+					super::ParameterMode::Immediate,
+					super::ParameterMode::Relative
+				)
+			})
+		);
+	}
+
+	#[test]
+	fn pgm_quine() {
+		let quine = vec![
+			109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+		];
+
+		assert_eq!(Intcode::from(quine.clone()).run_to_halt(), Ok(quine));
+	}
+
+	#[test]
+	fn pgm_large_multiply() {
+		let mut program = Intcode::from(vec![1102, 34_915_192, 34_915_192, 7, 4, 7, 99, 0]);
+
+		assert_eq!(program.run_to_halt(), Ok(vec![1_219_070_632_396_864]));
+	}
+
+	#[test]
+	fn pgm_large_literal() {
+		let mut program = Intcode::from(vec![104, 1_125_899_906_842_624, 99]);
+
+		assert_eq!(program.run_to_halt(), Ok(vec![1_125_899_906_842_624]));
+	}
+
+	#[test]
+	fn pgm_writes_past_end() {
+		// Move the relative base far past the end of the program, store 3 + 4 there, then read it
+		// back out.
+		let mut program = Intcode::from(vec![109, 1000, 21_101, 3, 4, 50, 204, 50, 99]);
+
+		assert_eq!(program.run_to_halt(), Ok(vec![7]));
+		assert_eq!(program.data().len(), 1051);
+		assert_eq!(program.data()[1050], 7);
+		assert_eq!(program.relative_base, 1000);
+	}
+
+	#[test]
+	fn self_modifying_code() {
+		// Output 7, overwrite the `out` with a `hlt`, then jump back to it.
+		let program = Intcode::from(vec![104, 7, 1101, 0, 99, 0, 1105, 1, 0]);
+
+		assert_eq!(program.clone().run_to_halt(), Ok(vec![7]));
+
+		// The rewrite only happened in the clone, so the original still runs its `out`.
+		assert_eq!(program.read(0), 104);
+		assert_eq!(program.clone().run_to_halt(), Ok(vec![7]));
+	}
+
+	#[test]
+	fn waits_for_input() {
+		// Echo two inputs, then halt.
+		let mut program = Intcode::from(vec![3, 0, 4, 0, 3, 0, 4, 0, 99]);
+
+		assert_eq!(program.run(), State::NeedsInput);
+		assert_eq!(program.run(), State::NeedsInput);
+		assert_eq!(program.head(), 0);
+
+		program.input(5);
+		assert_eq!(program.run(), State::Output(5));
+		assert_eq!(program.run(), State::NeedsInput);
+
+		program.input(6);
+		assert_eq!(program.run(), State::Output(6));
+		assert_eq!(program.run(), State::Halted);
+		assert_eq!(program.run(), State::Halted);
+	}
+
+	#[test]
+	fn faults() {
+		let mut program = Intcode::from(vec![1, 0, 0, 0, 42]);
+		assert_eq!(program.run(), State::Fault(Fault::UnknownOpcode(42)));
+		assert_eq!(program.head(), 4);
+
+		let mut program = Intcode::from(vec![1301, 0, 0, 0, 99]);
+		assert_eq!(program.step(), State::Fault(Fault::UnknownParameterMode(3)));
+
+		let mut program = Intcode::from(vec![11_101, 1, 2, 0, 99]);
+		assert_eq!(program.step(), State::Fault(Fault::ImmediateAddress));
+
+		let mut program = Intcode::from(vec![4, -1, 99]);
+		assert_eq!(program.step(), State::Fault(Fault::NegativeAddress(-1)));
+
+		let mut program = Intcode::from(vec![3, 0, 99]);
+		assert_eq!(program.run_to_halt(), Err(Fault::InputExhausted));
+
+		let mut program = Intcode::from(vec![1101, 1, 2, 1 << 40, 99]);
+		assert_eq!(
+			program.step(),
+			State::Fault(Fault::AddressTooLarge(1 << 40))
+		);
+
+		let mut program = Intcode::from(vec![3, 1 << 40, 99]);
+		program.input(5);
+		assert_eq!(
+			program.step(),
+			State::Fault(Fault::AddressTooLarge(1 << 40))
+		);
+		assert_eq!(
+			program.step(),
+			State::Fault(Fault::AddressTooLarge(1 << 40))
+		);
+		assert_eq!(program.input_queue(), &[5]);
+		assert_eq!(program.head(), 0);
+
+		let mut program = Intcode::from(vec![1101, i64::MAX, 1, 0, 99]);
+		assert_eq!(program.step(), State::Fault(Fault::Overflow));
+
+		let mut program = Intcode::from(vec![1102, i64::MAX, 2, 0, 99]);
+		assert_eq!(program.step(), State::Fault(Fault::Overflow));
+
+		let mut program = Intcode::from(vec![109, i64::MAX, 109, 1, 99]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.step(), State::Fault(Fault::Overflow));
+
+		let mut program = Intcode::from(vec![109, i64::MAX, 204, 1, 99]);
+		assert_eq!(program.step(), State::Running);
+		assert_eq!(program.step(), State::Fault(Fault::Overflow));
+	}
+
+	#[test]
+	fn console() {
+		let mut program = Intcode::from(vec![3, 0, 1002, 0, 2, 0, 4, 0, 99]);
+		let mut output = Vec::new();
+
+		Console::new("21\n".as_bytes(), &mut output)
+			.run(&mut program)
+			.unwrap();
+
+		assert_eq!(String::from_utf8(output).unwrap(), "<= => 42\n");
+
+		let error = Console::new("forty\n".as_bytes(), Vec::new())
+			.run(&mut Intcode::from(vec![3, 0, 99]))
+			.unwrap_err();
+
+		assert!(matches!(error, ConsoleError::InvalidInput(input) if input == "forty"));
 	}
 }
@@ -0,0 +1,270 @@
+//! Arcade cabinets, whose games draw on a screen of tiles and read a joystick.
+//!
+//! Games output triples: an `x` and `y` position and the tile to draw there, except that a
+//! position of `(-1, 0)` sets the score instead.  Whenever a game wants input it's reading the
+//! joystick, where `-1` tilts left, `0` is neutral and `1` tilts right.
+
+use core::fmt::Write;
+use std::collections::HashMap;
+
+use daocutil::viz::{self, Color, Glyph};
+
+use super::{Fault, Intcode, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+	Empty,
+	Wall,
+	Block,
+	Paddle,
+	Ball,
+}
+
+impl Tile {
+	fn glyph(self) -> Glyph {
+		match self {
+			Self::Empty => Glyph::from(' '),
+			Self::Wall => Glyph::from('#'),
+			Self::Block => Glyph::colored('=', Color::Cyan),
+			Self::Paddle => Glyph::colored('-', Color::Yellow),
+			Self::Ball => Glyph::colored('o', Color::Red),
+		}
+	}
+}
+
+impl TryFrom<i64> for Tile {
+	type Error = ArcadeError;
+
+	fn try_from(raw: i64) -> Result<Tile, ArcadeError> {
+		match raw {
+			0 => Ok(Self::Empty),
+			1 => Ok(Self::Wall),
+			2 => Ok(Self::Block),
+			3 => Ok(Self::Paddle),
+			4 => Ok(Self::Ball),
+			_ => Err(ArcadeError::UnknownTile(raw)),
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArcadeError {
+	#[error(transparent)]
+	Fault(#[from] Fault),
+	#[error("unknown tile {0}")]
+	UnknownTile(i64),
+	#[error("tile drawn off the screen at ({x}, {y})")]
+	OffScreen { x: i64, y: i64 },
+	#[error("game stopped partway through drawing a tile")]
+	Unfinished,
+}
+
+#[derive(Debug, Clone)]
+pub struct Arcade {
+	machine: Intcode,
+	screen: HashMap<(i32, i32), Tile>,
+	score: i64,
+	ball: Option<(i32, i32)>,
+	paddle: Option<(i32, i32)>,
+}
+
+impl Arcade {
+	pub fn new(machine: Intcode) -> Self {
+		Self {
+			machine,
+			screen: HashMap::new(),
+			score: 0,
+			ball: None,
+			paddle: None,
+		}
+	}
+
+	pub fn screen(&self) -> &HashMap<(i32, i32), Tile> {
+		&self.screen
+	}
+
+	pub fn score(&self) -> i64 {
+		self.score
+	}
+
+	/// The number of blocks left on the screen.
+	pub fn blocks(&self) -> usize {
+		self
+			.screen
+			.values()
+			.filter(|&&tile| tile == Tile::Block)
+			.count()
+	}
+
+	/// Queues a joystick position for the game to read.
+	pub fn joystick(&mut self, tilt: i64) {
+		self.machine.input(tilt);
+	}
+
+	/// Waits for the next value of a triple.
+	fn next_value(&mut self) -> Result<i64, ArcadeError> {
+		match self.machine.run() {
+			State::Output(value) => Ok(value),
+			State::Fault(fault) => Err(fault.into()),
+			_ => Err(ArcadeError::Unfinished),
+		}
+	}
+
+	/// Runs the game, drawing what it outputs, until it wants to read the joystick or halts.
+	/// Returns whether it halted.
+	///
+	/// # Errors
+	///
+	/// Fails if the game faults or draws something which can't be drawn.
+	pub fn update(&mut self) -> Result<bool, ArcadeError> {
+		loop {
+			let x = match self.machine.run() {
+				State::Output(x) => x,
+				State::NeedsInput => return Ok(false),
+				State::Halted => return Ok(true),
+				State::Fault(fault) => return Err(fault.into()),
+				State::Running => unreachable!("run never stops while running"),
+			};
+
+			let y = self.next_value()?;
+			let value = self.next_value()?;
+
+			if (x, y) == (-1, 0) {
+				self.score = value;
+				continue;
+			}
+
+			let position = i32::try_from(x)
+				.ok()
+				.zip(i32::try_from(y).ok())
+				.filter(|&(x, y)| x >= 0 && y >= 0)
+				.ok_or(ArcadeError::OffScreen { x, y })?;
+
+			let tile = Tile::try_from(value)?;
+
+			match tile {
+				Tile::Ball => self.ball = Some(position),
+				Tile::Paddle => self.paddle = Some(position),
+				_ => {}
+			}
+
+			self.screen.insert(position, tile);
+		}
+	}
+
+	/// Plays the game to the end by keeping the paddle under the ball, calling `on_frame` each
+	/// time the screen has been updated.  Returns the final score.
+	///
+	/// # Errors
+	///
+	/// Fails if the game faults or draws something which can't be drawn.
+	pub fn autoplay(&mut self, mut on_frame: impl FnMut(&Self)) -> Result<i64, ArcadeError> {
+		loop {
+			let halted = self.update()?;
+			on_frame(self);
+
+			if halted {
+				break Ok(self.score);
+			}
+
+			let tilt = match (self.ball, self.paddle) {
+				(Some((ball, _)), Some((paddle, _))) => i64::from((ball - paddle).signum()),
+				_ => 0,
+			};
+
+			self.joystick(tilt);
+		}
+	}
+
+	/// Renders the screen, with the score underneath.
+	pub fn frame(&self) -> String {
+		let mut frame = viz::render_sparse(
+			self
+				.screen
+				.iter()
+				.map(|(&position, &tile)| (position, tile.glyph())),
+			Tile::Empty.glyph(),
+		);
+
+		let _ = writeln!(frame, "Score: {}", self.score);
+
+		frame
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Arcade, ArcadeError, Tile};
+	use crate::intcode::{asm::assemble, Intcode};
+
+	fn arcade(source: &str) -> Arcade {
+		Arcade::new(Intcode::from(assemble(source).unwrap()))
+	}
+
+	#[test]
+	fn draws_tiles_and_score() {
+		let mut arcade = arcade(
+			"
+			out #1
+			out #2
+			out #3
+			out #6
+			out #5
+			out #4
+			out #-1
+			out #0
+			out #12345
+			hlt
+		",
+		);
+
+		assert_eq!(arcade.update(), Ok(true));
+		assert_eq!(arcade.screen()[&(1, 2)], Tile::Paddle);
+		assert_eq!(arcade.screen()[&(6, 5)], Tile::Ball);
+		assert_eq!(arcade.score(), 12_345);
+		assert_eq!(arcade.blocks(), 0);
+	}
+
+	#[test]
+	fn follows_the_ball() {
+		// Draws the paddle left of the ball, then scores whatever the joystick was set to.
+		let mut arcade = arcade(
+			"
+			out #0
+			out #3
+			out #3
+			out #4
+			out #1
+			out #4
+			in  [tilt]
+			out #-1
+			out #0
+			out [tilt]
+			hlt
+			tilt: data 0
+		",
+		);
+
+		let mut frames = 0;
+
+		assert_eq!(arcade.autoplay(|_| frames += 1), Ok(1));
+		assert_eq!(frames, 2);
+		assert!(arcade.frame().ends_with("Score: 1\n"));
+	}
+
+	#[test]
+	fn errors() {
+		assert_eq!(
+			arcade("out #1\nout #1\nout #9\nhlt").update(),
+			Err(ArcadeError::UnknownTile(9))
+		);
+		assert_eq!(
+			arcade("out #1\nout #-3\nout #0\nhlt").update(),
+			Err(ArcadeError::OffScreen { x: 1, y: -3 })
+		);
+		assert_eq!(
+			arcade("out #1\nout #1\nhlt").update(),
+			Err(ArcadeError::Unfinished)
+		);
+	}
+}
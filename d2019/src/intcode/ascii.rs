@@ -0,0 +1,266 @@
+//! Talking to programs which speak ASCII.
+//!
+//! Some programs read commands as lines of ASCII codes and print their responses the same way,
+//! with only the final answer (if any) output as a value too large to be a character.  [`Ascii`]
+//! wraps a machine to take care of the encoding, and can be driven either one exchange at a time,
+//! from a script, or interactively from the terminal.
+
+use std::{
+	error::Error,
+	io::{self, BufRead, IsTerminal, Write},
+};
+
+use super::{Fault, Intcode, Program, State};
+
+#[derive(thiserror::Error, Debug)]
+pub enum AsciiError {
+	#[error(transparent)]
+	Fault(#[from] Fault),
+	#[error("`{0}` can't be sent as ASCII")]
+	NotAscii(char),
+	#[error("failed to talk to the console")]
+	Io(#[from] io::Error),
+}
+
+/// What a program printed before stopping.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Response {
+	pub text: String,
+	/// The last value output which wasn't an ASCII code, which is usually the answer.
+	pub value: Option<i64>,
+	/// Whether the program halted, rather than stopping to wait for input.
+	pub halted: bool,
+}
+
+impl Response {
+	pub fn lines(&self) -> impl Iterator<Item = &str> {
+		self.text.lines()
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Ascii {
+	machine: Intcode,
+	transcript: String,
+}
+
+impl Ascii {
+	pub fn new(machine: Intcode) -> Self {
+		Self {
+			machine,
+			transcript: String::new(),
+		}
+	}
+
+	pub fn machine(&self) -> &Intcode {
+		&self.machine
+	}
+
+	/// Everything printed and sent so far, as it would have appeared on a terminal.
+	pub fn transcript(&self) -> &str {
+		&self.transcript
+	}
+
+	/// Queues `line` as ASCII codes, followed by a newline.
+	///
+	/// # Errors
+	///
+	/// Fails, without queueing anything, if `line` contains anything other than ASCII.
+	pub fn send_line(&mut self, line: &str) -> Result<(), AsciiError> {
+		if let Some(c) = line.chars().find(|c| !c.is_ascii()) {
+			return Err(AsciiError::NotAscii(c));
+		}
+
+		for byte in line.bytes().chain([b'\n']) {
+			self.machine.input(i64::from(byte));
+		}
+
+		self.transcript.push_str(line);
+		self.transcript.push('\n');
+
+		Ok(())
+	}
+
+	/// Runs the program until it halts or wants more input than has been sent.
+	///
+	/// # Errors
+	///
+	/// Fails if the program faults.
+	pub fn run(&mut self) -> Result<Response, AsciiError> {
+		let mut response = Response::default();
+
+		loop {
+			match self.machine.run() {
+				State::Output(value) => match u8::try_from(value) {
+					Ok(byte) if byte.is_ascii() => response.text.push(char::from(byte)),
+					_ => response.value = Some(value),
+				},
+				State::NeedsInput => break,
+				State::Halted => {
+					response.halted = true;
+					break;
+				}
+				State::Fault(fault) => return Err(fault.into()),
+				State::Running => unreachable!("run never stops while running"),
+			}
+		}
+
+		self.transcript.push_str(&response.text);
+
+		Ok(response)
+	}
+
+	/// Sends every line of a script up front, then runs the program.
+	///
+	/// # Errors
+	///
+	/// Fails if the script isn't ASCII or the program faults.
+	pub fn run_script<'s>(
+		&mut self,
+		script: impl IntoIterator<Item = &'s str>,
+	) -> Result<Response, AsciiError> {
+		for line in script {
+			self.send_line(line)?;
+		}
+
+		self.run()
+	}
+
+	/// Alternates between printing what the program says to `output` and sending it a line read
+	/// from `input`, until the program halts or `input` runs out.  Returns the program's answer,
+	/// if it gave one.
+	///
+	/// # Errors
+	///
+	/// Fails if the program faults, or the console can't be used.
+	pub fn interact(
+		&mut self,
+		mut input: impl BufRead,
+		mut output: impl Write,
+	) -> Result<Option<i64>, AsciiError> {
+		let mut answer = None;
+
+		loop {
+			let response = self.run()?;
+			write!(output, "{}", response.text)?;
+			answer = response.value.or(answer);
+
+			if response.halted {
+				if let Some(value) = response.value {
+					writeln!(output, "=> {value}")?;
+				}
+
+				return Ok(answer);
+			}
+
+			output.flush()?;
+
+			let mut line = String::new();
+
+			if input.read_line(&mut line)? == 0 {
+				return Ok(answer);
+			}
+
+			self.send_line(line.trim_end_matches(['\r', '\n']))?;
+		}
+	}
+}
+
+/// Talks to a program on the terminal, for the `ascii` subcommand.
+///
+/// Lines for the program are read from stdin, so the program has to come from a file rather
+/// than being piped in.
+///
+/// # Errors
+///
+/// Fails if stdin isn't a terminal, the input isn't a comma-separated list of integers, the
+/// program faults, or the terminal can't be used.
+pub fn command(data: &str) -> Result<(), Box<dyn Error>> {
+	if !io::stdin().is_terminal() {
+		return Err(
+			"the ASCII session reads lines from the terminal; give the program as a file".into(),
+		);
+	}
+
+	let mut ascii = Ascii::new(Intcode::from(data.parse::<Program>()?));
+	ascii.interact(io::stdin().lock(), io::stdout())?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Ascii, AsciiError, Response};
+	use crate::intcode::{asm::assemble, Intcode};
+
+	/// Prints a prompt, then echoes lines back until one is empty, then outputs 1234 and halts.
+	const ECHO: &str = "
+		        out  #63
+		        out  #10
+		read:   in   [char]
+		        eq   [char], #10, [done]
+		        jnz  [done], #line
+		        out  [char]
+		        eq   #1, #1, [started]
+		        jz   #0, #read
+		line:   jz   [started], #finish
+		        out  #10
+		        eq   #0, #1, [started]
+		        jz   #0, #read
+		finish: out  #1234
+		        hlt
+		char:   data 0
+		done:   data 0
+		started: data 0
+	";
+
+	fn echo() -> Ascii {
+		Ascii::new(Intcode::from(assemble(ECHO).unwrap()))
+	}
+
+	#[test]
+	fn exchanges() {
+		let mut ascii = echo();
+
+		let response = ascii.run().unwrap();
+		assert_eq!(response.lines().collect::<Vec<_>>(), vec!["?"]);
+		assert!(!response.halted);
+
+		ascii.send_line("hello").unwrap();
+		assert_eq!(
+			ascii.run().unwrap(),
+			Response {
+				text: "hello\n".to_string(),
+				value: None,
+				halted: false
+			}
+		);
+
+		assert!(matches!(
+			ascii.send_line("héllo"),
+			Err(AsciiError::NotAscii('é'))
+		));
+
+		let response = ascii.run_script(["a", "b", ""]).unwrap();
+		assert_eq!(response.text, "a\nb\n");
+		assert_eq!(response.value, Some(1234));
+		assert!(response.halted);
+
+		assert_eq!(ascii.transcript(), "?\nhello\nhello\na\nb\n\na\nb\n");
+	}
+
+	#[test]
+	fn interactive() {
+		let mut output = Vec::new();
+		let answer = echo().interact("one\ntwo\n\n".as_bytes(), &mut output);
+
+		assert_eq!(answer.unwrap(), Some(1234));
+		assert_eq!(String::from_utf8(output).unwrap(), "?\none\ntwo\n=> 1234\n");
+
+		// Running out of input leaves the program waiting.
+		assert_eq!(
+			echo().interact("one\n".as_bytes(), Vec::new()).unwrap(),
+			None
+		);
+	}
+}
@@ -0,0 +1,392 @@
+//! A small assembler, for writing Intcode programs by hand.
+//!
+//! Each line holds an optional `label:` followed by either an instruction or a `data` directive,
+//! and anything after a `;` is a comment.  Instructions use the same mnemonics and operand syntax
+//! as the [disassembler](super::disasm): `[12]` for position mode, `#5` for immediate mode and
+//! `rb+3` for relative mode.  Wherever a number is expected, a label can be used instead to stand
+//! for the address it marks.
+//!
+//! ```text
+//!         in   [count]
+//! loop:   out  [count]
+//!         add  [count], #-1, [count]
+//!         jnz  [count], #loop
+//!         hlt
+//! count:  data 0
+//! ```
+
+use std::collections::HashMap;
+
+use daocutil::parse::{ErrorKind, ParseError};
+
+use super::{Opcode, ParameterMode};
+
+/// A number, or a label standing in for one.  Labels keep the column they were found at, in case
+/// they turn out not to exist.
+#[derive(Debug)]
+enum Value<'s> {
+	Number(i64),
+	Label(usize, &'s str),
+}
+
+#[derive(Debug)]
+enum Statement<'s> {
+	Instruction {
+		opcode: Opcode,
+		operands: Vec<(ParameterMode, Value<'s>)>,
+	},
+	Data(Vec<Value<'s>>),
+}
+
+impl Statement<'_> {
+	fn size(&self) -> usize {
+		match self {
+			Self::Instruction { operands, .. } => 1 + operands.len(),
+			Self::Data(values) => values.len(),
+		}
+	}
+}
+
+/// The 1-based column at which `part`, a slice of `line`, starts.
+fn column(line: &str, part: &str) -> usize {
+	part.as_ptr() as usize - line.as_ptr() as usize + 1
+}
+
+fn is_label(name: &str) -> bool {
+	name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+		&& name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn value<'s>(line: &str, token: &'s str) -> Result<Value<'s>, ParseError> {
+	let column = column(line, token);
+
+	if is_label(token) {
+		Ok(Value::Label(column, token))
+	} else {
+		daocutil::parse::field((column, token)).map(Value::Number)
+	}
+}
+
+fn operand<'s>(line: &str, token: &'s str) -> Result<(ParameterMode, Value<'s>), ParseError> {
+	if let Some(value_token) = token.strip_prefix('#') {
+		return Ok((ParameterMode::Immediate, value(line, value_token)?));
+	}
+
+	if let Some(offset) = token.strip_prefix("rb") {
+		// Keep the sign as part of the number.
+		let offset = offset.strip_prefix('+').unwrap_or(offset);
+		return Ok((ParameterMode::Relative, value(line, offset)?));
+	}
+
+	if let Some(address) = token
+		.strip_prefix('[')
+		.and_then(|rest| rest.strip_suffix(']'))
+	{
+		return Ok((ParameterMode::Position, value(line, address.trim())?));
+	}
+
+	Err(ParseError::invalid(
+		column(line, token),
+		token,
+		"operands look like `[12]`, `#5` or `rb+3`",
+	))
+}
+
+/// Parses one line into its label (if any) and statement (if any).
+fn statement(line: &str) -> Result<(Option<&str>, Option<Statement<'_>>), ParseError> {
+	let code = line.split(';').next().unwrap_or_default();
+	let mut rest = code.trim();
+	let mut label = None;
+
+	if let Some((name, after)) = rest.split_once(':') {
+		let name = name.trim();
+
+		if !is_label(name) {
+			return Err(ParseError::invalid(
+				column(line, name),
+				name,
+				"not a valid label",
+			));
+		}
+
+		label = Some(name);
+		rest = after.trim();
+	}
+
+	if rest.is_empty() {
+		return Ok((label, None));
+	}
+
+	let (mnemonic, operands) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+	let operands: Vec<&str> = if operands.trim().is_empty() {
+		Vec::new()
+	} else {
+		operands.split(',').map(str::trim).collect()
+	};
+
+	if mnemonic == "data" {
+		let values = operands
+			.into_iter()
+			.map(|token| value(line, token))
+			.collect::<Result<_, _>>()?;
+
+		return Ok((label, Some(Statement::Data(values))));
+	}
+
+	let opcode = Opcode::from_mnemonic(mnemonic)
+		.ok_or_else(|| ParseError::invalid(column(line, mnemonic), mnemonic, "unknown mnemonic"))?;
+
+	if operands.len() != opcode.parameter_count() {
+		return Err(ParseError::invalid(
+			column(line, mnemonic),
+			mnemonic,
+			format!(
+				"takes {} operands, but was given {}",
+				opcode.parameter_count(),
+				operands.len()
+			),
+		));
+	}
+
+	let operands: Vec<(ParameterMode, Value)> = operands
+		.into_iter()
+		.map(|token| operand(line, token))
+		.collect::<Result<_, _>>()?;
+
+	if let Some(written) = opcode.written_parameter() {
+		if operands[written].0 == ParameterMode::Immediate {
+			return Err(ParseError::invalid(
+				column(line, mnemonic),
+				mnemonic,
+				"can't write to an immediate operand",
+			));
+		}
+	}
+
+	Ok((label, Some(Statement::Instruction { opcode, operands })))
+}
+
+/// Assembles `source` into a program.
+///
+/// # Errors
+///
+/// Fails on anything which can't be assembled, such as an unknown mnemonic, an operand of the
+/// wrong form or an unknown or repeated label.
+pub fn assemble(source: &str) -> Result<Vec<i64>, ParseError> {
+	let mut labels: HashMap<&str, i64> = HashMap::new();
+	let mut statements = Vec::new();
+	let mut address = 0;
+
+	// Find every statement's address up front, so that labels can be used before they're defined.
+	for (idx, line) in source.lines().enumerate() {
+		let (label, statement) = statement(line).map_err(|error| error.on_line(idx + 1))?;
+
+		if let Some(label) = label {
+			if labels.insert(label, address).is_some() {
+				return Err(
+					ParseError::invalid(column(line, label), label, "label is already defined")
+						.on_line(idx + 1),
+				);
+			}
+		}
+
+		if let Some(statement) = statement {
+			address += i64::try_from(statement.size()).unwrap();
+			statements.push((idx + 1, statement));
+		}
+	}
+
+	let resolve = |line: usize, value: &Value| -> Result<i64, ParseError> {
+		match *value {
+			Value::Number(number) => Ok(number),
+			Value::Label(column, label) => labels.get(label).copied().ok_or_else(|| {
+				ParseError::new(
+					column,
+					ErrorKind::Invalid {
+						token: label.to_string(),
+						reason: "unknown label".to_string(),
+					},
+				)
+				.on_line(line)
+			}),
+		}
+	};
+
+	let mut program = Vec::new();
+
+	for (line, statement) in statements {
+		match statement {
+			Statement::Instruction { opcode, operands } => {
+				let modes = operands
+					.iter()
+					.zip([100, 1000, 10_000])
+					.map(|((mode, _), place)| mode.code() * place)
+					.sum::<i64>();
+
+				program.push(opcode.code() + modes);
+
+				for (_, value) in &operands {
+					program.push(resolve(line, value)?);
+				}
+			}
+
+			Statement::Data(values) => {
+				for value in &values {
+					program.push(resolve(line, value)?);
+				}
+			}
+		}
+	}
+
+	Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::assemble;
+	use crate::intcode::{disasm, Intcode};
+
+	/// Assembles and runs a program on the given inputs, returning its outputs.
+	fn run(source: &str, inputs: &[i64]) -> Vec<i64> {
+		let mut program = Intcode::from(assemble(source).unwrap());
+
+		for &input in inputs {
+			program.input(input);
+		}
+
+		program.run_to_halt().unwrap()
+	}
+
+	#[test]
+	fn encoding() {
+		assert_eq!(
+			assemble("add [9], #3, rb-2\nout #-5 ; comment\nhlt"),
+			Ok(vec![21_001, 9, 3, -2, 104, -5, 99])
+		);
+	}
+
+	#[test]
+	fn labels_and_data() {
+		let source = "
+			        in   [count]
+			loop:   out  [count]
+			        add  [count], #-1, [count]
+			        jnz  [count], #loop
+			        hlt
+			count:  data 0
+		";
+
+		assert_eq!(
+			assemble(source),
+			Ok(vec![3, 12, 4, 12, 1001, 12, -1, 12, 1005, 12, 2, 99, 0])
+		);
+		assert_eq!(run(source, &[3]), vec![3, 2, 1]);
+	}
+
+	#[test]
+	fn arithmetic_and_comparisons() {
+		let source = "
+			add #2, #3, [out]
+			out [out]
+			mul #-4, [six], [out]
+			out [out]
+			lt  #1, #2, [out]
+			out [out]
+			lt  #2, #1, [out]
+			out [out]
+			eq  [six], #6, [out]
+			out [out]
+			eq  [six], #7, [out]
+			out [out]
+			hlt
+			six: data 6
+			out: data 0
+		";
+
+		assert_eq!(run(source, &[]), vec![5, -24, 1, 0, 1, 0]);
+	}
+
+	#[test]
+	fn jumps() {
+		let source = "
+			jz  #0, #taken
+			out #1
+			taken:
+			jnz #0, #not_taken
+			out #2
+			jz  #1, #not_taken
+			out #3
+			jnz #7, [target]
+			not_taken:
+			out #4
+			end:
+			hlt
+			target: data end
+		";
+
+		assert_eq!(run(source, &[]), vec![2, 3]);
+	}
+
+	#[test]
+	fn relative_mode() {
+		let source = "
+			arb #100
+			in  rb+0
+			arb #-98
+			add rb+98, rb-1, rb+98
+			out [100]
+			hlt
+		";
+
+		// rb-1 reads address 1, which holds the 100 from `arb #100`.
+		assert_eq!(run(source, &[5]), vec![105]);
+	}
+
+	#[test]
+	fn errors() {
+		let error = assemble("hlt\n  jump #1, #2").unwrap_err();
+		assert_eq!((error.line, error.column), (Some(2), 3));
+
+		let error = assemble("add #1, #2").unwrap_err();
+		assert_eq!(error.column, 1);
+
+		let error = assemble("add #1, #2, #3").unwrap_err();
+		assert!(error.to_string().contains("immediate"));
+
+		let error = assemble("out [nowhere]").unwrap_err();
+		assert_eq!((error.line, error.column), (Some(1), 6));
+
+		let error = assemble("a: hlt\na: hlt").unwrap_err();
+		assert_eq!(error.line, Some(2));
+	}
+
+	#[test]
+	fn round_trip() {
+		let source = "
+			start: in  [value]
+			       arb #1
+			       mul rb+4, #2, rb-1
+			       eq  [value], #0, [value]
+			       jz  [value], #start
+			       out #42
+			       hlt
+			value: data 0
+		";
+
+		let program = assemble(source).unwrap();
+		let listing: Vec<String> = disasm::disassemble(&program[..program.len() - 1])
+			.unwrap()
+			.into_iter()
+			.map(|line| line.item.to_string())
+			.collect();
+
+		let mut reassembled = assemble(&listing.join("\n")).unwrap();
+		reassembled.push(0);
+
+		assert_eq!(reassembled, program);
+
+		let mut machine = Intcode::from(program);
+		machine.input(0);
+		assert_eq!(machine.run_to_halt(), Ok(vec![42]));
+	}
+}
@@ -0,0 +1,671 @@
+//! Stepping through a program, for working out why it misbehaves.
+//!
+//! A [`Debugger`] wraps a machine and runs it one instruction at a time, stopping at breakpoints
+//! (on an address, or on any instruction with a given opcode) and watchpoints (on a memory cell
+//! changing), and optionally writing every instruction executed to a trace.  [`Repl`] drives a
+//! debugger from text commands; it's what the `--debug` flag of the binary runs.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	error::Error,
+	fs::File,
+	io::{self, BufRead, BufWriter, IsTerminal, Write},
+};
+
+use super::{disasm, Intcode, Opcode, Program, State};
+
+/// Where to stop before executing an instruction.
+#[derive(Debug, PartialEq)]
+pub enum Breakpoint {
+	Address(usize),
+	Opcode(Opcode),
+}
+
+/// A watched memory cell which changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+	pub address: usize,
+	pub old: i64,
+	pub new: i64,
+}
+
+/// Why the debugger stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+	/// The machine stopped by itself (or, after a single step, carried on running).
+	Machine(State),
+	/// The next instruction, at the given address, matches a breakpoint.
+	Breakpoint(usize),
+	/// The instruction just executed changed one or more watched memory cells, in ascending order
+	/// of address.
+	Watchpoints(Vec<Change>),
+}
+
+pub struct Debugger {
+	machine: Intcode,
+	breakpoints: Vec<Breakpoint>,
+	/// Watched addresses, along with the value each held when last checked.
+	watchpoints: BTreeMap<usize, i64>,
+	trace: Option<Box<dyn Write>>,
+	/// The breakpoint address last stopped at, which shouldn't stop the debugger again straight
+	/// away.
+	stopped_at: Option<usize>,
+}
+
+impl Debugger {
+	pub fn new(machine: Intcode) -> Self {
+		Self {
+			machine,
+			breakpoints: Vec::new(),
+			watchpoints: BTreeMap::new(),
+			trace: None,
+			stopped_at: None,
+		}
+	}
+
+	pub fn machine(&self) -> &Intcode {
+		&self.machine
+	}
+
+	pub fn machine_mut(&mut self) -> &mut Intcode {
+		&mut self.machine
+	}
+
+	pub fn into_machine(self) -> Intcode {
+		self.machine
+	}
+
+	pub fn breakpoints(&self) -> &[Breakpoint] {
+		&self.breakpoints
+	}
+
+	pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+		if !self.breakpoints.contains(&breakpoint) {
+			self.breakpoints.push(breakpoint);
+		}
+	}
+
+	/// Removes a breakpoint, returning whether it was set.
+	pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+		let before = self.breakpoints.len();
+		self.breakpoints.retain(|existing| existing != breakpoint);
+
+		self.breakpoints.len() != before
+	}
+
+	/// Watched addresses, in ascending order.
+	pub fn watchpoints(&self) -> impl Iterator<Item = usize> + '_ {
+		self.watchpoints.keys().copied()
+	}
+
+	pub fn watch(&mut self, address: usize) {
+		self.watchpoints.insert(address, self.machine.read(address));
+	}
+
+	/// Stops watching an address, returning whether it was watched.
+	pub fn unwatch(&mut self, address: usize) -> bool {
+		self.watchpoints.remove(&address).is_some()
+	}
+
+	/// Writes every instruction executed from now on to `trace`, one per line.
+	pub fn trace_to(&mut self, trace: impl Write + 'static) {
+		self.trace = Some(Box::new(trace));
+	}
+
+	/// Stops tracing, flushing anything written so far.
+	///
+	/// # Errors
+	///
+	/// Fails if the trace couldn't be flushed.
+	pub fn stop_tracing(&mut self) -> io::Result<()> {
+		match self.trace.take() {
+			Some(mut trace) => trace.flush(),
+			None => Ok(()),
+		}
+	}
+
+	/// Whether a breakpoint matches the instruction about to be executed.
+	fn at_breakpoint(&self) -> bool {
+		let head = self.machine.head();
+		let opcode = Opcode::try_from(self.machine.read(head) % 100).ok();
+
+		self.breakpoints.iter().any(|breakpoint| match breakpoint {
+			Breakpoint::Address(address) => *address == head,
+			Breakpoint::Opcode(wanted) => opcode.as_ref() == Some(wanted),
+		})
+	}
+
+	/// The instruction at the head, as the disassembler would show it.
+	fn current_instruction(&self) -> String {
+		let head = self.machine.head();
+
+		// Only the instruction itself is needed, which saves copying all of memory out.
+		let window: Vec<i64> = (head..head + 4)
+			.map(|address| self.machine.read(address))
+			.collect();
+
+		disasm::decode(&window, 0)
+			.unwrap_or(disasm::Item::Data(window[0]))
+			.to_string()
+	}
+
+	/// Executes a single instruction, ignoring breakpoints.
+	pub fn step(&mut self) -> Stop {
+		if self.trace.is_some() {
+			let line = format!(
+				"{:>6}  rb={:<6} {}",
+				self.machine.head(),
+				self.machine.relative_base(),
+				self.current_instruction(),
+			);
+
+			let failed = self
+				.trace
+				.as_mut()
+				.is_some_and(|trace| writeln!(trace, "{line}").is_err());
+
+			// A broken trace shouldn't stop the program being debugged.
+			if failed {
+				self.trace = None;
+			}
+		}
+
+		let state = self.machine.step();
+		let mut changes = Vec::new();
+
+		// Every watched cell is checked, so that all the changes made by one instruction are
+		// reported together.
+		for (&address, old) in &mut self.watchpoints {
+			let new = self.machine.read(address);
+
+			if new != *old {
+				changes.push(Change {
+					address,
+					old: *old,
+					new,
+				});
+				*old = new;
+			}
+		}
+
+		if changes.is_empty() {
+			Stop::Machine(state)
+		} else {
+			Stop::Watchpoints(changes)
+		}
+	}
+
+	/// Runs until a breakpoint or watchpoint is hit, or the machine outputs something, needs
+	/// input, halts or faults.  Resuming from a breakpoint executes the instruction it stopped at,
+	/// rather than stopping there again.
+	pub fn resume(&mut self) -> Stop {
+		let mut skip = self.stopped_at.take();
+
+		loop {
+			let head = self.machine.head();
+
+			if skip.take() != Some(head) && self.at_breakpoint() {
+				self.stopped_at = Some(head);
+				return Stop::Breakpoint(head);
+			}
+
+			match self.step() {
+				Stop::Machine(State::Running) => {}
+				stop => return stop,
+			}
+		}
+	}
+}
+
+const HELP: &str = "\
+commands:
+  s, step [n]          execute n instructions (default 1)
+  c, continue          run until something happens
+  b, break <addr|op>   stop before an address, or any instruction with a mnemonic
+  d, delete <addr|op>  remove a breakpoint
+  w, watch <addr>      stop when a memory cell changes
+  u, unwatch <addr>    stop watching a memory cell
+  i, input <values>    queue input values
+  r, regs              show the head, relative base, input queue, breakpoints and watchpoints
+  m, mem <addr> [n]    show n memory cells (default 8)
+  l, list [addr] [n]   disassemble n instructions (default 8) from addr (default the head)
+  t, trace <file|off>  write every instruction executed to a file
+  q, quit              stop debugging
+";
+
+/// The most memory cells `mem` will show at once.
+const MAX_CELLS_SHOWN: usize = 1024;
+
+/// Drives a [`Debugger`] from commands read line by line from `input`, writing responses to
+/// `output`.
+pub struct Repl<R, W> {
+	debugger: Debugger,
+	input: R,
+	output: W,
+}
+
+impl<R: BufRead, W: Write> Repl<R, W> {
+	pub fn new(debugger: Debugger, input: R, output: W) -> Self {
+		Self {
+			debugger,
+			input,
+			output,
+		}
+	}
+
+	pub fn debugger(&self) -> &Debugger {
+		&self.debugger
+	}
+
+	/// Reads and runs commands until told to quit or the input ends.
+	///
+	/// # Errors
+	///
+	/// Fails if reading commands or writing responses fails.
+	pub fn run(&mut self) -> io::Result<()> {
+		writeln!(self.output, "{}", self.describe_head())?;
+
+		loop {
+			write!(self.output, "(dbg) ")?;
+			self.output.flush()?;
+
+			let mut line = String::new();
+
+			if self.input.read_line(&mut line)? == 0 {
+				break Ok(());
+			}
+
+			let mut words = line.split_whitespace();
+
+			let Some(command) = words.next() else {
+				continue;
+			};
+
+			let args: Vec<&str> = words.collect();
+
+			match self.command(command, &args) {
+				Ok(true) => {}
+				Ok(false) => break Ok(()),
+				Err(CommandError::Io(error)) => break Err(error),
+				Err(CommandError::Usage(message)) => writeln!(self.output, "{message}")?,
+			}
+		}
+	}
+
+	/// Runs a single command, returning whether to keep going.
+	fn command(&mut self, command: &str, args: &[&str]) -> Result<bool, CommandError> {
+		match (command, args) {
+			("s" | "step", []) => self.step(1)?,
+			("s" | "step", [count]) => self.step(number(count)?)?,
+			("c" | "continue", []) => {
+				let stop = self.debugger.resume();
+				self.report(stop)?;
+			}
+
+			("b" | "break", [target]) => self.debugger.add_breakpoint(breakpoint(target)?),
+			("d" | "delete", [target]) => {
+				if !self.debugger.remove_breakpoint(&breakpoint(target)?) {
+					writeln!(self.output, "no such breakpoint")?;
+				}
+			}
+
+			("w" | "watch", [address]) => self.debugger.watch(number(address)?),
+			("u" | "unwatch", [address]) => {
+				if !self.debugger.unwatch(number(address)?) {
+					writeln!(self.output, "no such watchpoint")?;
+				}
+			}
+
+			("i" | "input", values) if !values.is_empty() => {
+				for value in values {
+					let value = value
+						.parse()
+						.map_err(|_| CommandError::Usage(format!("`{value}` is not a number")))?;
+					self.debugger.machine_mut().input(value);
+				}
+			}
+
+			("r" | "regs", []) => self.registers()?,
+			("m" | "mem", [address]) => self.memory(number(address)?, 8)?,
+			("m" | "mem", [address, count]) => self.memory(number(address)?, number(count)?)?,
+			("l" | "list", []) => self.list(self.debugger.machine().head(), 8)?,
+			("l" | "list", [address]) => self.list(number(address)?, 8)?,
+			("l" | "list", [address, count]) => self.list(number(address)?, number(count)?)?,
+			("t" | "trace", ["off"]) => self.debugger.stop_tracing()?,
+			("t" | "trace", [path]) => {
+				self.debugger.stop_tracing()?;
+				self.debugger.trace_to(BufWriter::new(File::create(path)?));
+			}
+
+			("q" | "quit", []) => return Ok(false),
+			("h" | "help", _) => write!(self.output, "{HELP}")?,
+			_ => {
+				return Err(CommandError::Usage(format!(
+					"unknown command `{}`; try `help`",
+					[command]
+						.iter()
+						.chain(args)
+						.copied()
+						.collect::<Vec<_>>()
+						.join(" ")
+				)))
+			}
+		}
+
+		Ok(true)
+	}
+
+	fn describe_head(&self) -> String {
+		format!(
+			"{:>6}  {}",
+			self.debugger.machine().head(),
+			self.debugger.current_instruction()
+		)
+	}
+
+	fn step(&mut self, count: usize) -> io::Result<()> {
+		for _ in 0..count {
+			match self.debugger.step() {
+				Stop::Machine(State::Running) => {}
+				stop => return self.report(stop),
+			}
+		}
+
+		writeln!(self.output, "{}", self.describe_head())
+	}
+
+	fn report(&mut self, stop: Stop) -> io::Result<()> {
+		match stop {
+			Stop::Machine(State::Running) => {}
+			Stop::Machine(State::Output(value)) => writeln!(self.output, "output {value}")?,
+			Stop::Machine(State::NeedsInput) => writeln!(self.output, "waiting for input")?,
+			Stop::Machine(State::Halted) => writeln!(self.output, "halted")?,
+			Stop::Machine(State::Fault(fault)) => writeln!(self.output, "fault: {fault}")?,
+			Stop::Breakpoint(address) => writeln!(self.output, "breakpoint at {address}")?,
+			Stop::Watchpoints(changes) => {
+				for Change { address, old, new } in changes {
+					writeln!(self.output, "[{address}] changed from {old} to {new}")?;
+				}
+			}
+		}
+
+		writeln!(self.output, "{}", self.describe_head())
+	}
+
+	fn registers(&mut self) -> io::Result<()> {
+		let machine = self.debugger.machine();
+
+		writeln!(self.output, "head  {}", machine.head())?;
+		writeln!(self.output, "rb    {}", machine.relative_base())?;
+		writeln!(self.output, "input {:?}", machine.input_queue())?;
+		writeln!(self.output, "break {:?}", self.debugger.breakpoints())?;
+		writeln!(
+			self.output,
+			"watch {:?}",
+			self.debugger.watchpoints().collect::<BTreeSet<_>>()
+		)
+	}
+
+	fn memory(&mut self, address: usize, count: usize) -> Result<(), CommandError> {
+		if count > MAX_CELLS_SHOWN {
+			return Err(CommandError::Usage(format!(
+				"can show at most {MAX_CELLS_SHOWN} cells at a time"
+			)));
+		}
+
+		let end = address.checked_add(count).ok_or_else(|| {
+			CommandError::Usage(format!("{count} cells from {address} is out of range"))
+		})?;
+
+		let machine = self.debugger.machine();
+		let values: Vec<String> = (address..end)
+			.map(|address| machine.read(address).to_string())
+			.collect();
+
+		writeln!(self.output, "{address:>6}  {}", values.join(", "))?;
+
+		Ok(())
+	}
+
+	fn list(&mut self, address: usize, count: usize) -> io::Result<()> {
+		let data = &self.debugger.machine().data();
+		let mut address = address;
+
+		for _ in 0..count {
+			if address >= data.len() {
+				break;
+			}
+
+			let item = disasm::decode(data, address).unwrap_or(disasm::Item::Data(data[address]));
+			let marker = if address == self.debugger.machine().head() {
+				'>'
+			} else {
+				' '
+			};
+
+			writeln!(self.output, "{marker} {address:>5}  {item}")?;
+			address += item.size();
+		}
+
+		Ok(())
+	}
+}
+
+enum CommandError {
+	Io(io::Error),
+	Usage(String),
+}
+
+impl From<io::Error> for CommandError {
+	fn from(error: io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
+fn number(word: &str) -> Result<usize, CommandError> {
+	word
+		.parse()
+		.map_err(|_| CommandError::Usage(format!("`{word}` is not an address or count")))
+}
+
+fn breakpoint(word: &str) -> Result<Breakpoint, CommandError> {
+	if let Some(opcode) = Opcode::from_mnemonic(word) {
+		return Ok(Breakpoint::Opcode(opcode));
+	}
+
+	number(word).map(Breakpoint::Address)
+}
+
+/// Debugs a program interactively on the terminal, for the `--debug` flag.
+///
+/// Commands are read from stdin, so the program has to come from a file rather than being piped
+/// in.
+///
+/// # Errors
+///
+/// Fails if stdin isn't a terminal, the input isn't a comma-separated list of integers, or the
+/// terminal can't be used.
+pub fn command(data: &str) -> Result<(), Box<dyn Error>> {
+	if !io::stdin().is_terminal() {
+		return Err("the debugger reads commands from the terminal; give the program as a file".into());
+	}
+
+	let program: Program = data.parse()?;
+	let debugger = Debugger::new(Intcode::from(program));
+
+	Repl::new(debugger, io::stdin().lock(), io::stdout()).run()?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		cell::RefCell,
+		io::{self, Write},
+		rc::Rc,
+	};
+
+	use super::{Breakpoint, Change, Debugger, Repl, Stop};
+	use crate::intcode::{asm::assemble, Intcode, Opcode, State};
+
+	const COUNTDOWN: &str = "
+		        in   [count]
+		loop:   out  [count]
+		        add  [count], #-1, [count]
+		        jnz  [count], #loop
+		        hlt
+		count:  data 0
+	";
+
+	fn debugger(inputs: &[i64]) -> Debugger {
+		let mut machine = Intcode::from(assemble(COUNTDOWN).unwrap());
+
+		for &input in inputs {
+			machine.input(input);
+		}
+
+		Debugger::new(machine)
+	}
+
+	#[test]
+	fn breakpoints() {
+		let mut debugger = debugger(&[2]);
+		debugger.add_breakpoint(Breakpoint::Address(4));
+
+		assert_eq!(debugger.resume(), Stop::Machine(State::Output(2)));
+		assert_eq!(debugger.resume(), Stop::Breakpoint(4));
+		// Resuming from a breakpoint executes it rather than stopping straight away.
+		assert_eq!(debugger.resume(), Stop::Machine(State::Output(1)));
+
+		assert!(debugger.remove_breakpoint(&Breakpoint::Address(4)));
+		debugger.add_breakpoint(Breakpoint::Opcode(Opcode::Halt));
+
+		assert_eq!(debugger.resume(), Stop::Breakpoint(11));
+		assert_eq!(debugger.resume(), Stop::Machine(State::Halted));
+	}
+
+	#[test]
+	fn watchpoints() {
+		let mut debugger = debugger(&[2]);
+		debugger.watch(12);
+
+		assert_eq!(
+			debugger.resume(),
+			Stop::Watchpoints(vec![Change {
+				address: 12,
+				old: 0,
+				new: 2
+			}])
+		);
+		assert_eq!(debugger.machine().head(), 2);
+		assert_eq!(debugger.resume(), Stop::Machine(State::Output(2)));
+		assert_eq!(
+			debugger.resume(),
+			Stop::Watchpoints(vec![Change {
+				address: 12,
+				old: 2,
+				new: 1
+			}])
+		);
+	}
+
+	#[test]
+	fn watchpoints_changing_together() {
+		let mut debugger = debugger(&[2]);
+		debugger.watch(12);
+		debugger.watch(13);
+		debugger.machine_mut().write(13, 7).unwrap();
+
+		assert_eq!(
+			debugger.step(),
+			Stop::Watchpoints(vec![
+				Change {
+					address: 12,
+					old: 0,
+					new: 2
+				},
+				Change {
+					address: 13,
+					old: 0,
+					new: 7
+				},
+			])
+		);
+		assert_eq!(debugger.step(), Stop::Machine(State::Output(2)));
+	}
+
+	/// A writer which can still be read from after being handed to the debugger.
+	#[derive(Clone, Default)]
+	struct Shared(Rc<RefCell<Vec<u8>>>);
+
+	impl Write for Shared {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.borrow_mut().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn trace() {
+		let mut debugger = debugger(&[1]);
+		let trace = Shared::default();
+		debugger.trace_to(trace.clone());
+
+		assert_eq!(debugger.resume(), Stop::Machine(State::Output(1)));
+		assert_eq!(debugger.resume(), Stop::Machine(State::Halted));
+
+		let trace = String::from_utf8(trace.0.take()).unwrap();
+		let trace: Vec<&str> = trace.lines().map(str::trim).collect();
+
+		assert_eq!(
+			trace,
+			vec![
+				"0  rb=0      in   [12]",
+				"2  rb=0      out  [12]",
+				"4  rb=0      add  [12], #-1, [12]",
+				"8  rb=0      jnz  [12], #2",
+				"11  rb=0      hlt",
+			]
+		);
+	}
+
+	#[test]
+	fn repl_session() {
+		let commands =
+			"b hlt\ni 3\nc\nstep 2\nmem 12 2\nmem 0 18446744073709551614\nmem 18446744073709551615 2\nr\nbogus\nc\nc\nc\nc\nq\n";
+		let mut output = Vec::new();
+
+		Repl::new(debugger(&[]), commands.as_bytes(), &mut output)
+			.run()
+			.unwrap();
+
+		let output = String::from_utf8(output).unwrap();
+		let responses: Vec<&str> = output
+			.split("(dbg) ")
+			.map(str::trim_end)
+			.filter(|response| !response.is_empty())
+			.collect();
+
+		assert_eq!(
+			responses,
+			vec![
+				"     0  in   [12]",
+				"output 3\n     4  add  [12], #-1, [12]",
+				"     2  out  [12]",
+				"    12  2, 0",
+				"can show at most 1024 cells at a time",
+				"2 cells from 18446744073709551615 is out of range",
+				"head  2\nrb    0\ninput []\nbreak [Opcode(Halt)]\nwatch {}",
+				"unknown command `bogus`; try `help`",
+				"output 2\n     4  add  [12], #-1, [12]",
+				"output 1\n     4  add  [12], #-1, [12]",
+				"breakpoint at 11\n    11  hlt",
+				"halted\n    11  hlt",
+			]
+		);
+	}
+}
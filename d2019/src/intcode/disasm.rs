@@ -0,0 +1,293 @@
+//! Turning Intcode programs back into something readable.
+//!
+//! Every address is decoded in turn, so anything after the program's code (or any data mixed into
+//! it) is decoded as instructions too if it happens to look like some.  A listing shows each
+//! instruction's address, mnemonic and operands, with operands written as `[12]` in position mode,
+//! `#5` in immediate mode and `rb+3` in relative mode.  Addresses which are the target of an
+//! immediate jump are marked with a `>`.
+
+use core::fmt::{self, Write};
+use std::{collections::HashSet, error::Error};
+
+use super::{Fault, Instruction, Opcode, ParameterMode, Program};
+
+#[derive(Debug, PartialEq)]
+pub struct Operand {
+	pub mode: ParameterMode,
+	pub value: i64,
+}
+
+impl fmt::Display for Operand {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.mode {
+			ParameterMode::Position => write!(f, "[{}]", self.value),
+			ParameterMode::Immediate => write!(f, "#{}", self.value),
+			ParameterMode::Relative => write!(f, "rb{:+}", self.value),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Item {
+	Instruction {
+		opcode: Opcode,
+		operands: Vec<Operand>,
+	},
+	/// A value which couldn't be decoded as an instruction.
+	Data(i64),
+}
+
+impl Item {
+	/// How many addresses the item covers.
+	pub fn size(&self) -> usize {
+		match self {
+			Self::Instruction { operands, .. } => 1 + operands.len(),
+			Self::Data(_) => 1,
+		}
+	}
+
+	/// The address this item jumps to, if it's a jump to a known address.
+	pub fn jump_target(&self) -> Option<usize> {
+		match self {
+			Self::Instruction {
+				opcode: Opcode::JumpIfTrue | Opcode::JumpIfFalse,
+				operands,
+			} => match operands.as_slice() {
+				[_, Operand {
+					mode: ParameterMode::Immediate,
+					value,
+				}] => usize::try_from(*value).ok(),
+				_ => None,
+			},
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for Item {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Instruction { opcode, operands } => {
+				if operands.is_empty() {
+					return write!(f, "{}", opcode.mnemonic());
+				}
+
+				write!(f, "{:<4} ", opcode.mnemonic())?;
+
+				for (idx, operand) in operands.iter().enumerate() {
+					let separator = if idx == 0 { "" } else { ", " };
+					write!(f, "{separator}{operand}")?;
+				}
+
+				Ok(())
+			}
+			Self::Data(value) => write!(f, "data {value}"),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Line {
+	pub address: usize,
+	pub item: Item,
+	/// Whether some instruction in the program jumps here.
+	pub jump_target: bool,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisassemblyError {
+	#[error("{fault} at address {address}")]
+	Invalid { address: usize, fault: Fault },
+	#[error("instruction at address {0} runs past the end of the program")]
+	Truncated(usize),
+}
+
+/// Decodes the instruction at `address`.
+///
+/// # Errors
+///
+/// Fails if the value there isn't a valid instruction, or the program ends before all of the
+/// instruction's operands.
+pub fn decode(program: &[i64], address: usize) -> Result<Item, DisassemblyError> {
+	let Instruction {
+		opcode,
+		parameter_modes,
+	} = Instruction::try_from(
+		*program
+			.get(address)
+			.ok_or(DisassemblyError::Truncated(address))?,
+	)
+	.map_err(|fault| DisassemblyError::Invalid { address, fault })?;
+
+	let count = opcode.parameter_count();
+
+	let Some(values) = program.get(address + 1..address + 1 + count) else {
+		return Err(DisassemblyError::Truncated(address));
+	};
+
+	let (mode_0, mode_1, mode_2) = parameter_modes;
+	let operands: Vec<Operand> = [mode_0, mode_1, mode_2]
+		.into_iter()
+		.zip(values)
+		.map(|(mode, &value)| Operand { mode, value })
+		.collect();
+
+	// The instruction would fault when run, so it's probably not an instruction at all.
+	if let Some(written) = opcode.written_parameter() {
+		if operands[written].mode == ParameterMode::Immediate {
+			return Err(DisassemblyError::Invalid {
+				address,
+				fault: Fault::ImmediateAddress,
+			});
+		}
+	}
+
+	Ok(Item::Instruction { opcode, operands })
+}
+
+fn mark_jump_targets(items: Vec<(usize, Item)>) -> Vec<Line> {
+	let targets: HashSet<usize> = items
+		.iter()
+		.filter_map(|(_, item)| item.jump_target())
+		.collect();
+
+	items
+		.into_iter()
+		.map(|(address, item)| Line {
+			address,
+			item,
+			jump_target: targets.contains(&address),
+		})
+		.collect()
+}
+
+/// Decodes the whole program as instructions.
+///
+/// # Errors
+///
+/// Fails at the first value which isn't a valid instruction.
+pub fn disassemble(program: &[i64]) -> Result<Vec<Line>, DisassemblyError> {
+	let mut items = Vec::new();
+	let mut address = 0;
+
+	while address < program.len() {
+		let item = decode(program, address)?;
+		let size = item.size();
+
+		items.push((address, item));
+		address += size;
+	}
+
+	Ok(mark_jump_targets(items))
+}
+
+/// Decodes the program as instructions where possible, treating any value which isn't a valid
+/// instruction as data and carrying on from the next address.
+pub fn disassemble_lossy(program: &[i64]) -> Vec<Line> {
+	let mut items = Vec::new();
+	let mut address = 0;
+
+	while address < program.len() {
+		let item = decode(program, address).unwrap_or(Item::Data(program[address]));
+		let size = item.size();
+
+		items.push((address, item));
+		address += size;
+	}
+
+	mark_jump_targets(items)
+}
+
+/// Renders lines as a listing, one per line.
+pub fn listing(lines: &[Line]) -> String {
+	let width = lines
+		.last()
+		.map_or(1, |line| line.address.to_string().len());
+
+	let mut out = String::new();
+
+	for line in lines {
+		let marker = if line.jump_target { '>' } else { ' ' };
+		let _ = writeln!(out, "{marker} {:0width$}  {}", line.address, line.item);
+	}
+
+	out
+}
+
+/// Prints a listing of a program, for the `disasm` subcommand.
+///
+/// # Errors
+///
+/// Fails if the input isn't a comma-separated list of integers.
+pub fn command(data: &str) -> Result<(), Box<dyn Error>> {
+	let program: Program = data.parse()?;
+
+	print!("{}", listing(&disassemble_lossy(program.as_slice())));
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{disassemble, disassemble_lossy, listing, DisassemblyError, Fault, Item};
+
+	#[test]
+	fn operand_modes() {
+		let lines = disassemble(&[21_101, 3, -4, 5, 99]).unwrap();
+
+		assert_eq!(lines[0].item.to_string(), "add  #3, #-4, rb+5");
+		assert_eq!(lines[1].item.to_string(), "hlt");
+	}
+
+	#[test]
+	fn annotated_listing() {
+		// The day 5 example which outputs 999, 1000 or 1001 depending on how its input compares
+		// with 8, cut off before its final `hlt`.
+		let program = [
+			3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+			1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20, 1105,
+			1, 46, 98,
+		];
+
+		let expected = "  00  in   [21]
+  02  eq   [21], #8, [20]
+  06  jnz  [20], #22
+  09  lt   #8, [21], [20]
+  13  jz   [20], #31
+  16  jz   #0, #36
+  19  data 98
+  20  data 0
+  21  data 0
+> 22  mul  [21], #125, [20]
+  26  out  [20]
+  28  jnz  #1, #46
+> 31  out  #999
+  33  jnz  #1, #46
+> 36  add  #1000, #1, [20]
+  40  out  [20]
+  42  jnz  #1, #46
+  45  data 98
+";
+
+		assert_eq!(listing(&disassemble_lossy(&program)), expected);
+	}
+
+	#[test]
+	fn strict_errors() {
+		assert_eq!(
+			disassemble(&[1, 0, 0, 0, 98]),
+			Err(DisassemblyError::Invalid {
+				address: 4,
+				fault: Fault::UnknownOpcode(98)
+			})
+		);
+		assert_eq!(
+			disassemble(&[1101, 1, 2]),
+			Err(DisassemblyError::Truncated(0))
+		);
+		assert_eq!(
+			disassemble_lossy(&[11_101, 1, 2, 3])[0].item,
+			Item::Data(11_101)
+		);
+	}
+}
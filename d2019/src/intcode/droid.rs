@@ -0,0 +1,250 @@
+//! Remote-controlled droids, which report what they bump into as they're driven around.
+//!
+//! The program reads a movement command (`1` north, `2` south, `3` west, `4` east) and answers
+//! with a status: `0` if the droid hit a wall and stayed put, `1` if it moved, or `2` if it moved
+//! and found the oxygen system.  Since machines are cheap to clone, [`Map::explore`] maps the
+//! whole area breadth-first by cloning the droid at each junction rather than backtracking.
+
+use std::collections::{HashMap, VecDeque};
+
+use daocutil::viz::{self, Color, Glyph};
+
+use super::{robot::Position, Fault, Intcode, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	North,
+	South,
+	West,
+	East,
+}
+
+impl Direction {
+	pub const ALL: [Self; 4] = [Self::North, Self::South, Self::West, Self::East];
+
+	/// The movement command which moves the droid this way.
+	pub fn command(self) -> i64 {
+		match self {
+			Self::North => 1,
+			Self::South => 2,
+			Self::West => 3,
+			Self::East => 4,
+		}
+	}
+
+	/// The position one step from `(x, y)` in this direction, with `y` increasing southwards.
+	pub fn advance(self, (x, y): Position) -> Position {
+		match self {
+			Self::North => (x, y - 1),
+			Self::South => (x, y + 1),
+			Self::West => (x - 1, y),
+			Self::East => (x + 1, y),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+	Wall,
+	Open,
+	Oxygen,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DroidError {
+	#[error(transparent)]
+	Fault(#[from] Fault),
+	#[error("droid stopped responding to commands")]
+	Unresponsive,
+	#[error("unknown status {0}")]
+	UnknownStatus(i64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Droid {
+	machine: Intcode,
+}
+
+impl Droid {
+	pub fn new(machine: Intcode) -> Self {
+		Self { machine }
+	}
+
+	/// Tries to move one step, returning what the droid found there.  On a wall, the droid stays
+	/// where it was.
+	///
+	/// # Errors
+	///
+	/// Fails if the program faults, or doesn't answer with a known status.
+	pub fn command(&mut self, direction: Direction) -> Result<Cell, DroidError> {
+		self.machine.input(direction.command());
+
+		match self.machine.run() {
+			State::Output(0) => Ok(Cell::Wall),
+			State::Output(1) => Ok(Cell::Open),
+			State::Output(2) => Ok(Cell::Oxygen),
+			State::Output(status) => Err(DroidError::UnknownStatus(status)),
+			State::Fault(fault) => Err(fault.into()),
+			_ => Err(DroidError::Unresponsive),
+		}
+	}
+}
+
+/// Everything a droid found, relative to where it started.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Map {
+	cells: HashMap<Position, Cell>,
+	oxygen: Option<Position>,
+}
+
+impl Map {
+	/// Drives `droid` down every passage reachable from where it starts.
+	///
+	/// # Errors
+	///
+	/// Fails if the droid misbehaves anywhere along the way.
+	pub fn explore(droid: Droid) -> Result<Self, DroidError> {
+		let mut map = Self::default();
+		map.cells.insert((0, 0), Cell::Open);
+
+		let mut queue = VecDeque::from([((0, 0), droid)]);
+
+		while let Some((position, droid)) = queue.pop_front() {
+			for direction in Direction::ALL {
+				let next = direction.advance(position);
+
+				if map.cells.contains_key(&next) {
+					continue;
+				}
+
+				let mut droid = droid.clone();
+				let cell = droid.command(direction)?;
+				map.cells.insert(next, cell);
+
+				match cell {
+					Cell::Wall => {}
+					Cell::Open => queue.push_back((next, droid)),
+					Cell::Oxygen => {
+						map.oxygen = Some(next);
+						queue.push_back((next, droid));
+					}
+				}
+			}
+		}
+
+		Ok(map)
+	}
+
+	pub fn cells(&self) -> &HashMap<Position, Cell> {
+		&self.cells
+	}
+
+	/// Where the oxygen system is, if the droid found it.
+	pub fn oxygen(&self) -> Option<Position> {
+		self.oxygen
+	}
+
+	/// The number of steps from `start` to every position reachable from it.
+	pub fn distances(&self, start: Position) -> HashMap<Position, usize> {
+		let mut distances = HashMap::from([(start, 0)]);
+		let mut queue = VecDeque::from([start]);
+
+		while let Some(position) = queue.pop_front() {
+			let distance = distances[&position];
+
+			for next in Direction::ALL.map(|direction| direction.advance(position)) {
+				let passable = matches!(self.cells.get(&next), Some(Cell::Open | Cell::Oxygen));
+
+				if passable && !distances.contains_key(&next) {
+					distances.insert(next, distance + 1);
+					queue.push_back(next);
+				}
+			}
+		}
+
+		distances
+	}
+
+	/// Renders the map, with the droid's starting point marked.
+	pub fn frame(&self) -> String {
+		viz::render_sparse(
+			self.cells.iter().map(|(&position, &cell)| {
+				let glyph = match cell {
+					_ if position == (0, 0) => Glyph::colored('D', Color::Yellow),
+					Cell::Wall => Glyph::from('#'),
+					Cell::Open => Glyph::from('.'),
+					Cell::Oxygen => Glyph::colored('O', Color::Cyan),
+				};
+
+				(position, glyph)
+			}),
+			Glyph::from(' '),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Cell, Direction, Droid, DroidError, Map};
+	use crate::intcode::{asm::assemble, Intcode};
+
+	/// A droid in a corridor running east from its start, with the oxygen system two steps along
+	/// and walls everywhere else.
+	const CORRIDOR: &str = "
+		loop:  in   [cmd]
+		       eq   [cmd], #4, [east]
+		       jnz  [east], #move
+		       eq   [cmd], #3, [west]
+		       jz   [west], #wall
+		       jz   [x], #wall
+		       add  [x], #-1, [x]
+		       jz   #0, #report
+		move:  eq   [x], #2, [wall_ahead]
+		       jnz  [wall_ahead], #wall
+		       add  [x], #1, [x]
+		report: eq  [x], #2, [status]
+		       add  [status], #1, [status]
+		       out  [status]
+		       jz   #0, #loop
+		wall:  out  #0
+		       jz   #0, #loop
+		cmd:   data 0
+		east:  data 0
+		west:  data 0
+		x:     data 0
+		wall_ahead: data 0
+		status: data 0
+	";
+
+	#[test]
+	fn explores_a_corridor() {
+		let droid = Droid::new(Intcode::from(assemble(CORRIDOR).unwrap()));
+		let map = Map::explore(droid).unwrap();
+
+		assert_eq!(map.oxygen(), Some((2, 0)));
+		assert_eq!(map.cells()[&(1, 0)], Cell::Open);
+		assert_eq!(map.cells()[&(3, 0)], Cell::Wall);
+		assert_eq!(map.cells()[&(0, -1)], Cell::Wall);
+		assert_eq!(map.distances((0, 0))[&(2, 0)], 2);
+		assert_eq!(map.distances((2, 0)).len(), 3);
+
+		let frame = map.frame();
+		assert!(frame.contains('#'));
+		assert_eq!(frame.lines().count(), 3);
+	}
+
+	#[test]
+	fn errors() {
+		let mut droid = Droid::new(Intcode::from(assemble("in [5]\nout #7\nhlt").unwrap()));
+		assert_eq!(
+			droid.command(Direction::North),
+			Err(DroidError::UnknownStatus(7))
+		);
+
+		let mut droid = Droid::new(Intcode::from(assemble("hlt").unwrap()));
+		assert_eq!(
+			droid.command(Direction::North),
+			Err(DroidError::Unresponsive)
+		);
+	}
+}
@@ -0,0 +1,212 @@
+//! Copy-on-write memory, so that machines can be cloned cheaply.
+//!
+//! Memory is split into fixed-size pages behind reference counts.  Cloning memory only copies the
+//! page pointers, and a page is only copied when it's written to while shared, so branching a
+//! machine costs roughly as much as the memory the branch goes on to change.
+//!
+//! Each page also caches the instructions decoded from its cells, so that loops don't decode the
+//! same instructions over and over.  Writing to a cell drops whatever was decoded from it, which
+//! keeps self-modifying programs working.  The cache lives alongside the cells it was decoded
+//! from, so clones sharing a page share its cache too.
+//!
+//! Memory is capped at [`MEMORY_LIMIT`] cells, so that a stray write far past the end of a
+//! program faults instead of trying to allocate an enormous page table.  Pages between the end
+//! of memory and a write past it all share a single page of zeros until written to themselves.
+
+use std::{array, cell::Cell, rc::Rc};
+
+use super::{Fault, Instruction};
+
+/// The number of cells in each page.
+pub const PAGE_SIZE: usize = 64;
+
+/// The number of cells a program can write to.  No puzzle comes anywhere close.
+pub const MEMORY_LIMIT: usize = 1 << 24;
+
+#[derive(Debug, Clone)]
+struct Page {
+	cells: [i64; PAGE_SIZE],
+	decoded: [Cell<Option<Instruction>>; PAGE_SIZE],
+}
+
+impl Page {
+	/// A page holding `values`, followed by zeros.
+	fn new(values: &[i64]) -> Self {
+		let mut cells = [0; PAGE_SIZE];
+		cells[..values.len()].copy_from_slice(values);
+
+		Self {
+			cells,
+			decoded: array::from_fn(|_| Cell::new(None)),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+	pages: Vec<Rc<Page>>,
+	len: usize,
+}
+
+impl Memory {
+	/// The number of cells up to and including the highest one ever written to.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Reads the value at `address`.  Cells which were never written to read as zero.
+	pub fn read(&self, address: usize) -> i64 {
+		self
+			.pages
+			.get(address / PAGE_SIZE)
+			.map_or(0, |page| page.cells[address % PAGE_SIZE])
+	}
+
+	/// Writes `value` to `address`, growing memory to fit it if needed.  If the page holding
+	/// `address` is shared with a clone, it's copied first.
+	///
+	/// # Errors
+	///
+	/// Fails if `address` is past [`MEMORY_LIMIT`].
+	pub fn write(&mut self, address: usize, value: i64) -> Result<(), Fault> {
+		if address >= MEMORY_LIMIT {
+			return Err(Fault::AddressTooLarge(address));
+		}
+
+		let page = address / PAGE_SIZE;
+
+		if page >= self.pages.len() {
+			let blank = Rc::new(Page::new(&[]));
+			self.pages.resize_with(page + 1, || Rc::clone(&blank));
+		}
+
+		let page = Rc::make_mut(&mut self.pages[page]);
+		page.cells[address % PAGE_SIZE] = value;
+		page.decoded[address % PAGE_SIZE].set(None);
+
+		self.len = self.len.max(address + 1);
+
+		Ok(())
+	}
+
+	/// Decodes the instruction at `address`, reusing the last decoding if the cell hasn't been
+	/// written to since.
+	///
+	/// # Errors
+	///
+	/// Fails if the value at `address` isn't a valid instruction.
+	pub fn decode(&self, address: usize) -> Result<Instruction, Fault> {
+		let Some(page) = self.pages.get(address / PAGE_SIZE) else {
+			return Instruction::try_from(0);
+		};
+
+		let cached = &page.decoded[address % PAGE_SIZE];
+
+		if let Some(instruction) = cached.get() {
+			return Ok(instruction);
+		}
+
+		let instruction = Instruction::try_from(page.cells[address % PAGE_SIZE])?;
+		cached.set(Some(instruction));
+
+		Ok(instruction)
+	}
+
+	/// Copies every cell out into a vector.
+	pub fn to_vec(&self) -> Vec<i64> {
+		self
+			.pages
+			.iter()
+			.flat_map(|page| page.cells.iter().copied())
+			.take(self.len)
+			.collect()
+	}
+
+	/// The number of pages this memory still shares with `other`.
+	pub fn shared_pages(&self, other: &Memory) -> usize {
+		self
+			.pages
+			.iter()
+			.zip(&other.pages)
+			.filter(|(a, b)| Rc::ptr_eq(a, b))
+			.count()
+	}
+}
+
+impl From<Vec<i64>> for Memory {
+	fn from(cells: Vec<i64>) -> Self {
+		let pages = cells
+			.chunks(PAGE_SIZE)
+			.map(|chunk| Rc::new(Page::new(chunk)))
+			.collect();
+
+		Self {
+			pages,
+			len: cells.len(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::rc::Rc;
+
+	use super::{Memory, MEMORY_LIMIT, PAGE_SIZE};
+	use crate::intcode::Fault;
+
+	#[test]
+	fn reads_and_writes() {
+		let mut memory = Memory::from(vec![1, 2, 3]);
+
+		assert_eq!(memory.read(2), 3);
+		assert_eq!(memory.read(10_000), 0);
+
+		memory.write(PAGE_SIZE * 3 + 1, 7).unwrap();
+
+		assert_eq!(memory.len(), PAGE_SIZE * 3 + 2);
+		assert_eq!(memory.read(PAGE_SIZE * 3 + 1), 7);
+		assert_eq!(&memory.to_vec()[..4], &[1, 2, 3, 0]);
+		assert_eq!(memory.to_vec().len(), memory.len());
+	}
+
+	#[test]
+	fn clones_share_until_written() {
+		let original = Memory::from((0..i64::try_from(PAGE_SIZE * 4).unwrap()).collect::<Vec<_>>());
+		let mut branch = original.clone();
+
+		assert_eq!(branch.shared_pages(&original), 4);
+
+		branch.write(PAGE_SIZE + 5, -1).unwrap();
+
+		assert_eq!(branch.shared_pages(&original), 3);
+		assert_eq!(branch.read(PAGE_SIZE + 5), -1);
+		assert_eq!(
+			original.read(PAGE_SIZE + 5),
+			i64::try_from(PAGE_SIZE + 5).unwrap()
+		);
+	}
+
+	#[test]
+	fn writes_are_limited() {
+		let mut memory = Memory::from(vec![1, 2, 3]);
+
+		assert_eq!(
+			memory.write(1 << 40, 1),
+			Err(Fault::AddressTooLarge(1 << 40))
+		);
+		assert_eq!(memory.len(), 3);
+
+		// Everything between the program and a distant write shares one page of zeros.
+		memory.write(MEMORY_LIMIT - 1, 1).unwrap();
+		assert_eq!(memory.read(MEMORY_LIMIT - 1), 1);
+		assert_eq!(memory.read(MEMORY_LIMIT / 2), 0);
+		assert!(Rc::ptr_eq(
+			&memory.pages[1],
+			&memory.pages[MEMORY_LIMIT / PAGE_SIZE - 2]
+		));
+	}
+}
@@ -0,0 +1,376 @@
+//! Running several machines at once, with the values they output routed to each other.
+//!
+//! A [`Network`] owns the machines and gives each a turn in order, running it until it needs
+//! input it doesn't have.  Where each output goes is up to a [`Topology`]: a [`Chain`] of
+//! machines each feeding the next (optionally looping back around), or a [`PacketNetwork`] of
+//! machines sending addressed packets to each other.
+
+use std::{collections::HashMap, ops::ControlFlow};
+
+use super::{Fault, Intcode, State};
+
+/// Deliveries queued by a [`Topology`], to be handed to machines once it returns.
+#[derive(Debug)]
+pub struct Mailbox {
+	machines: usize,
+	deliveries: Vec<(usize, i64)>,
+}
+
+impl Mailbox {
+	/// The number of machines on the network.
+	pub fn machines(&self) -> usize {
+		self.machines
+	}
+
+	/// Queues `value` as input for machine `to`.
+	pub fn send(&mut self, to: usize, value: i64) {
+		self.deliveries.push((to, value));
+	}
+}
+
+/// Decides how values move between the machines of a [`Network`].
+pub trait Topology {
+	/// Handles `value`, just output by machine `from`.  Breaking stops the network.
+	fn route(&mut self, from: usize, value: i64, mailbox: &mut Mailbox) -> ControlFlow<()>;
+
+	/// Offers a value to machine `machine`, which wants input and has none queued.  By default
+	/// the machine is left waiting.
+	fn starved(&mut self, _machine: usize) -> Option<i64> {
+		None
+	}
+
+	/// Called when a whole round passes without any machine sending or receiving anything.
+	/// Breaking stops the network; if nothing is sent either way, the network is stuck and stops
+	/// as [`Outcome::Idle`].
+	fn idle(&mut self, _mailbox: &mut Mailbox) -> ControlFlow<()> {
+		ControlFlow::Continue(())
+	}
+}
+
+/// Why a network stopped running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+	/// Every machine halted.
+	Halted,
+	/// The topology asked to stop.
+	Stopped,
+	/// Nothing was moving, and the topology had nothing to add.
+	Idle,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkError {
+	#[error("machine {machine} faulted: {fault}")]
+	Fault { machine: usize, fault: Fault },
+	#[error("attempted to send to machine {0}, which doesn't exist")]
+	NoSuchMachine(usize),
+}
+
+#[derive(Debug)]
+pub struct Network<T> {
+	machines: Vec<Intcode>,
+	halted: Vec<bool>,
+	/// Whether each machine's queue starts with a value handed out by [`Topology::starved`],
+	/// rather than one actually sent to it.
+	fed_fallback: Vec<bool>,
+	topology: T,
+}
+
+impl<T: Topology> Network<T> {
+	pub fn new(machines: Vec<Intcode>, topology: T) -> Self {
+		let count = machines.len();
+
+		Self {
+			machines,
+			halted: vec![false; count],
+			fed_fallback: vec![false; count],
+			topology,
+		}
+	}
+
+	/// Queues a value for machine `machine` from outside of the network.
+	pub fn input(&mut self, machine: usize, value: i64) {
+		self.machines[machine].input(value);
+	}
+
+	pub fn machines(&self) -> &[Intcode] {
+		&self.machines
+	}
+
+	pub fn topology(&self) -> &T {
+		&self.topology
+	}
+
+	/// Runs the machines round-robin until they all halt, the topology stops them, or nothing is
+	/// happening any more.
+	///
+	/// # Errors
+	///
+	/// Fails if any machine faults, or a value is sent to a machine that doesn't exist.
+	pub fn run(&mut self) -> Result<Outcome, NetworkError> {
+		let mut mailbox = Mailbox {
+			machines: self.machines.len(),
+			deliveries: Vec::new(),
+		};
+
+		loop {
+			let mut active = false;
+
+			for id in 0..self.machines.len() {
+				if self.halted[id] {
+					continue;
+				}
+
+				let queued = self.machines[id].pending_input();
+				active |= queued > usize::from(self.fed_fallback[id]);
+				self.fed_fallback[id] = false;
+
+				loop {
+					match self.machines[id].run() {
+						State::Output(value) => {
+							active = true;
+
+							let flow = self.topology.route(id, value, &mut mailbox);
+							self.deliver(&mut mailbox)?;
+
+							if flow.is_break() {
+								return Ok(Outcome::Stopped);
+							}
+						}
+
+						State::NeedsInput => {
+							if let Some(value) = self.topology.starved(id) {
+								self.machines[id].input(value);
+								self.fed_fallback[id] = true;
+							}
+
+							break;
+						}
+
+						State::Halted => {
+							self.halted[id] = true;
+							break;
+						}
+
+						State::Fault(fault) => return Err(NetworkError::Fault { machine: id, fault }),
+						State::Running => unreachable!("run never stops while running"),
+					}
+				}
+			}
+
+			if self.halted.iter().all(|&halted| halted) {
+				return Ok(Outcome::Halted);
+			}
+
+			if !active {
+				let flow = self.topology.idle(&mut mailbox);
+
+				if flow.is_break() {
+					return Ok(Outcome::Stopped);
+				}
+
+				if mailbox.deliveries.is_empty() {
+					return Ok(Outcome::Idle);
+				}
+
+				self.deliver(&mut mailbox)?;
+			}
+		}
+	}
+
+	fn deliver(&mut self, mailbox: &mut Mailbox) -> Result<(), NetworkError> {
+		for (to, value) in mailbox.deliveries.drain(..) {
+			self
+				.machines
+				.get_mut(to)
+				.ok_or(NetworkError::NoSuchMachine(to))?
+				.input(value);
+		}
+
+		Ok(())
+	}
+}
+
+/// Machines in a line, each one's output becoming the next one's input.  In a ring, the last
+/// machine feeds the first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chain {
+	ring: bool,
+	last_output: Option<i64>,
+}
+
+impl Chain {
+	pub fn pipeline() -> Self {
+		Self::default()
+	}
+
+	pub fn ring() -> Self {
+		Self {
+			ring: true,
+			..Self::default()
+		}
+	}
+
+	/// The most recent value output by the last machine.
+	pub fn last_output(&self) -> Option<i64> {
+		self.last_output
+	}
+}
+
+impl Topology for Chain {
+	fn route(&mut self, from: usize, value: i64, mailbox: &mut Mailbox) -> ControlFlow<()> {
+		if from + 1 < mailbox.machines() {
+			mailbox.send(from + 1, value);
+		} else {
+			self.last_output = Some(value);
+
+			if self.ring {
+				mailbox.send(0, value);
+			}
+		}
+
+		ControlFlow::Continue(())
+	}
+}
+
+/// The address packets are sent to in order to reach the NAT.
+pub const NAT_ADDRESS: usize = 255;
+
+/// Machines exchanging `(x, y)` packets, each output as three values: the destination address,
+/// then `x`, then `y`.  A machine with nothing to receive reads `-1`.
+///
+/// Packets sent to [`NAT_ADDRESS`] are held by the NAT.  Without a NAT, the first such packet
+/// stops the network.  With one, whenever the network goes idle the NAT sends the last packet
+/// it received to machine 0, and the network stops once it sends the same `y` twice in a row.
+#[derive(Debug, Clone, Default)]
+pub struct PacketNetwork {
+	nat: bool,
+	partial: HashMap<usize, Vec<i64>>,
+	first_to_nat: Option<(i64, i64)>,
+	held: Option<(i64, i64)>,
+	last_woken: Option<i64>,
+	repeated: Option<i64>,
+}
+
+impl PacketNetwork {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_nat() -> Self {
+		Self {
+			nat: true,
+			..Self::default()
+		}
+	}
+
+	/// The first packet sent to the NAT.
+	pub fn first_to_nat(&self) -> Option<(i64, i64)> {
+		self.first_to_nat
+	}
+
+	/// The `y` value the NAT sent to machine 0 twice in a row.
+	pub fn repeated(&self) -> Option<i64> {
+		self.repeated
+	}
+}
+
+impl Topology for PacketNetwork {
+	fn route(&mut self, from: usize, value: i64, mailbox: &mut Mailbox) -> ControlFlow<()> {
+		let partial = self.partial.entry(from).or_default();
+		partial.push(value);
+
+		let &[to, x, y] = partial.as_slice() else {
+			return ControlFlow::Continue(());
+		};
+
+		partial.clear();
+
+		if usize::try_from(to) == Ok(NAT_ADDRESS) {
+			self.first_to_nat.get_or_insert((x, y));
+			self.held = Some((x, y));
+
+			return if self.nat {
+				ControlFlow::Continue(())
+			} else {
+				ControlFlow::Break(())
+			};
+		}
+
+		// A negative address can't be anything, so send it past the end of the network to be
+		// reported as missing.
+		let to = usize::try_from(to).unwrap_or(usize::MAX);
+		mailbox.send(to, x);
+		mailbox.send(to, y);
+
+		ControlFlow::Continue(())
+	}
+
+	fn starved(&mut self, _machine: usize) -> Option<i64> {
+		Some(-1)
+	}
+
+	fn idle(&mut self, mailbox: &mut Mailbox) -> ControlFlow<()> {
+		let Some((x, y)) = self.held.filter(|_| self.nat) else {
+			return ControlFlow::Continue(());
+		};
+
+		if self.last_woken == Some(y) {
+			self.repeated = Some(y);
+			return ControlFlow::Break(());
+		}
+
+		self.last_woken = Some(y);
+		mailbox.send(0, x);
+		mailbox.send(0, y);
+
+		ControlFlow::Continue(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Chain, Intcode, Network, Outcome};
+
+	// Reads a value, adds one to it and outputs it, then halts.
+	const INCREMENT: [i64; 9] = [3, 0, 1001, 0, 1, 0, 4, 0, 99];
+
+	fn incrementers(count: usize, topology: Chain) -> Network<Chain> {
+		let machines = (0..count)
+			.map(|_| Intcode::from(INCREMENT.to_vec()))
+			.collect();
+
+		Network::new(machines, topology)
+	}
+
+	#[test]
+	fn pipeline() {
+		let mut network = incrementers(4, Chain::pipeline());
+		network.input(0, 10);
+
+		assert_eq!(network.run(), Ok(Outcome::Halted));
+		assert_eq!(network.topology().last_output(), Some(14));
+	}
+
+	#[test]
+	fn stuck_pipeline_goes_idle() {
+		let mut network = incrementers(3, Chain::pipeline());
+
+		assert_eq!(network.run(), Ok(Outcome::Idle));
+		assert_eq!(network.topology().last_output(), None);
+	}
+
+	#[test]
+	fn ring_feeds_back() {
+		// Two machines which each pass on three incremented values before halting.
+		let program = vec![
+			3, 100, 1001, 100, 1, 100, 4, 100, 1001, 101, 1, 101, 1007, 101, 3, 102, 1005, 102, 0, 99,
+		];
+		let machines = vec![Intcode::from(program.clone()), Intcode::from(program)];
+		let mut network = Network::new(machines, Chain::ring());
+		network.input(0, 0);
+
+		assert_eq!(network.run(), Ok(Outcome::Halted));
+		assert_eq!(network.topology().last_output(), Some(6));
+	}
+}
@@ -0,0 +1,154 @@
+//! Loading programs from puzzle input.
+//!
+//! Every Intcode day's input is a single line of comma-separated integers, which parses into a
+//! [`Program`].  A program is only the initial contents of memory; turn it into an [`Intcode`]
+//! machine to run it.
+
+use core::{num::ParseIntError, str::FromStr};
+
+use super::Intcode;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProgramError {
+	#[error("program is empty")]
+	Empty,
+	#[error("value {index} (`{token}`) isn't an integer: {source}")]
+	InvalidValue {
+		index: usize,
+		token: String,
+		source: ParseIntError,
+	},
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Program(Vec<i64>);
+
+impl Program {
+	pub fn as_slice(&self) -> &[i64] {
+		&self.0
+	}
+
+	pub fn into_vec(self) -> Vec<i64> {
+		self.0
+	}
+
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// A copy of the program with each `(address, value)` written into it.  Addresses past the end
+	/// of the program grow it, with zeros in between.
+	#[must_use]
+	pub fn patched(&self, patches: &[(usize, i64)]) -> Self {
+		let mut cells = self.0.clone();
+
+		for &(address, value) in patches {
+			if address >= cells.len() {
+				cells.resize(address + 1, 0);
+			}
+
+			cells[address] = value;
+		}
+
+		Self(cells)
+	}
+
+	/// A copy of the program with its noun and verb, the values at addresses 1 and 2, replaced.
+	#[must_use]
+	pub fn with_noun_verb(&self, noun: i64, verb: i64) -> Self {
+		self.patched(&[(1, noun), (2, verb)])
+	}
+}
+
+impl FromStr for Program {
+	type Err = ProgramError;
+
+	fn from_str(s: &str) -> Result<Self, ProgramError> {
+		let s = s.trim();
+
+		if s.is_empty() {
+			return Err(ProgramError::Empty);
+		}
+
+		s.split(',')
+			.map(str::trim)
+			.enumerate()
+			.map(|(index, token)| {
+				token.parse().map_err(|source| ProgramError::InvalidValue {
+					index,
+					token: token.to_string(),
+					source,
+				})
+			})
+			.collect::<Result<_, _>>()
+			.map(Self)
+	}
+}
+
+impl From<Vec<i64>> for Program {
+	fn from(cells: Vec<i64>) -> Self {
+		Self(cells)
+	}
+}
+
+impl From<Program> for Intcode {
+	fn from(program: Program) -> Self {
+		Self::from(program.0)
+	}
+}
+
+impl From<&Program> for Intcode {
+	fn from(program: &Program) -> Self {
+		Self::from(program.0.clone())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Program, ProgramError};
+
+	#[test]
+	fn parses() {
+		assert_eq!(
+			"1,0,0,3,99".parse(),
+			Ok(Program::from(vec![1, 0, 0, 3, 99]))
+		);
+		assert_eq!(
+			" 104, -7 ,\t99\n\n".parse(),
+			Ok(Program::from(vec![104, -7, 99]))
+		);
+	}
+
+	#[test]
+	fn reports_bad_values() {
+		assert_eq!("\n".parse::<Program>(), Err(ProgramError::Empty));
+
+		let error = "1,2,x3,4".parse::<Program>().unwrap_err();
+		assert!(matches!(
+			&error,
+			ProgramError::InvalidValue { index: 2, token, .. } if token == "x3"
+		));
+		assert!(error.to_string().starts_with("value 2 (`x3`)"));
+
+		assert!(matches!(
+			"1,,2".parse::<Program>(),
+			Err(ProgramError::InvalidValue { index: 1, .. })
+		));
+	}
+
+	#[test]
+	fn patches() {
+		let program = Program::from(vec![1, 0, 0, 0, 99]);
+
+		assert_eq!(program.with_noun_verb(12, 2).as_slice(), &[1, 12, 2, 0, 99]);
+		assert_eq!(
+			program.patched(&[(6, 5)]).as_slice(),
+			&[1, 0, 0, 0, 99, 0, 5]
+		);
+		assert_eq!(program.len(), 5);
+	}
+}
@@ -0,0 +1,107 @@
+//! Undoing execution, for exploring a program by trial and error.
+
+use super::Intcode;
+
+/// A machine along with a log of checkpoints it can be rewound to.  Each checkpoint is a clone of
+/// the machine, which is cheap since memory is copy-on-write.
+#[derive(Debug, Clone)]
+pub struct RewindLog {
+	machine: Intcode,
+	checkpoints: Vec<Intcode>,
+}
+
+impl RewindLog {
+	pub fn new(machine: Intcode) -> Self {
+		Self {
+			machine,
+			checkpoints: Vec::new(),
+		}
+	}
+
+	pub fn machine(&self) -> &Intcode {
+		&self.machine
+	}
+
+	pub fn machine_mut(&mut self) -> &mut Intcode {
+		&mut self.machine
+	}
+
+	/// The number of checkpoints which can be rewound to.
+	pub fn depth(&self) -> usize {
+		self.checkpoints.len()
+	}
+
+	/// Records the machine's current state, returning the depth it can be rewound to with
+	/// [`rewind_to`](Self::rewind_to).
+	pub fn checkpoint(&mut self) -> usize {
+		self.checkpoints.push(self.machine.clone());
+		self.checkpoints.len() - 1
+	}
+
+	/// Restores the most recent checkpoint, discarding it.  Returns `false` if there was none.
+	pub fn rewind(&mut self) -> bool {
+		match self.checkpoints.pop() {
+			Some(machine) => {
+				self.machine = machine;
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Restores the checkpoint at `depth`, discarding it and every later one.  Returns `false` if
+	/// there's no such checkpoint.
+	pub fn rewind_to(&mut self, depth: usize) -> bool {
+		if depth >= self.checkpoints.len() {
+			return false;
+		}
+
+		self.checkpoints.truncate(depth + 1);
+		self.rewind()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::RewindLog;
+	use crate::intcode::{asm::assemble, Intcode, State};
+
+	#[test]
+	fn rewinding_undoes_everything() {
+		// Adds each input to a running total, outputting the total each time.
+		let program = assemble(
+			"
+			loop: in  [value]
+			      add [total], [value], [total]
+			      out [total]
+			      jnz #1, #loop
+			value: data 0
+			total: data 0
+			",
+		)
+		.unwrap();
+
+		let mut log = RewindLog::new(Intcode::from(program));
+		log.machine_mut().input(5);
+		assert_eq!(log.machine_mut().run(), State::Output(5));
+
+		let before = log.checkpoint();
+		log.machine_mut().input(10);
+		assert_eq!(log.machine_mut().run(), State::Output(15));
+
+		log.checkpoint();
+		log.machine_mut().input(100);
+		assert_eq!(log.machine_mut().run(), State::Output(115));
+		assert_eq!(log.depth(), 2);
+
+		// Back to just after the first output, and on down a different branch.
+		assert!(log.rewind_to(before));
+		assert_eq!(log.depth(), 0);
+		assert_eq!(log.machine().read(12), 5);
+
+		log.machine_mut().input(1);
+		assert_eq!(log.machine_mut().run(), State::Output(6));
+		assert_eq!(log.machine_mut().run(), State::NeedsInput);
+		assert!(!log.rewind());
+	}
+}
@@ -0,0 +1,205 @@
+//! Robots which wander a grid under the control of a program.
+//!
+//! Each cycle, the robot's camera sends the program the color of the panel underneath it, and the
+//! program answers with two values: the color to paint that panel, then which way to turn (`0`
+//! for left, `1` for right).  The robot then moves forward one panel.  The grid is sparse, with
+//! panels which were never painted reading as `0`.
+
+use std::collections::HashMap;
+
+use super::{Fault, Intcode, State};
+
+/// A panel's position, with `y` increasing downwards.
+pub type Position = (i32, i32);
+
+/// The panels which have been painted, and their colors.
+pub type Panels = HashMap<Position, i64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heading {
+	Up,
+	Right,
+	Down,
+	Left,
+}
+
+impl Heading {
+	#[must_use]
+	pub fn turn_left(self) -> Self {
+		match self {
+			Self::Up => Self::Left,
+			Self::Right => Self::Up,
+			Self::Down => Self::Right,
+			Self::Left => Self::Down,
+		}
+	}
+
+	#[must_use]
+	pub fn turn_right(self) -> Self {
+		match self {
+			Self::Up => Self::Right,
+			Self::Right => Self::Down,
+			Self::Down => Self::Left,
+			Self::Left => Self::Up,
+		}
+	}
+
+	/// The position one step from `(x, y)` in this direction.
+	pub fn advance(self, (x, y): Position) -> Position {
+		match self {
+			Self::Up => (x, y - 1),
+			Self::Right => (x + 1, y),
+			Self::Down => (x, y + 1),
+			Self::Left => (x - 1, y),
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RobotError {
+	#[error(transparent)]
+	Fault(#[from] Fault),
+	#[error("program wanted more input before painting")]
+	Stalled,
+	#[error("program halted between painting and turning")]
+	Unfinished,
+	#[error("unknown turn direction {0}")]
+	UnknownTurn(i64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Robot {
+	machine: Intcode,
+	position: Position,
+	heading: Heading,
+}
+
+impl Robot {
+	/// A robot at the origin, facing up.
+	pub fn new(machine: Intcode) -> Self {
+		Self {
+			machine,
+			position: (0, 0),
+			heading: Heading::Up,
+		}
+	}
+
+	pub fn position(&self) -> Position {
+		self.position
+	}
+
+	pub fn heading(&self) -> Heading {
+		self.heading
+	}
+
+	/// Runs the program until it produces a value, or halts.
+	fn next_output(&mut self) -> Result<Option<i64>, RobotError> {
+		match self.machine.run() {
+			State::Output(value) => Ok(Some(value)),
+			State::Halted => Ok(None),
+			State::NeedsInput => Err(RobotError::Stalled),
+			State::Fault(fault) => Err(fault.into()),
+			State::Running => unreachable!("run never stops while running"),
+		}
+	}
+
+	/// Runs one cycle: look, paint, turn and move.  Returns whether the program is still going.
+	///
+	/// # Errors
+	///
+	/// Fails if the program faults or doesn't follow the protocol.
+	pub fn step(&mut self, panels: &mut Panels) -> Result<bool, RobotError> {
+		let color = panels.get(&self.position).copied().unwrap_or_default();
+		self.machine.input(color);
+
+		let Some(paint) = self.next_output()? else {
+			return Ok(false);
+		};
+
+		panels.insert(self.position, paint);
+
+		self.heading = match self.next_output()?.ok_or(RobotError::Unfinished)? {
+			0 => self.heading.turn_left(),
+			1 => self.heading.turn_right(),
+			turn => return Err(RobotError::UnknownTurn(turn)),
+		};
+		self.position = self.heading.advance(self.position);
+
+		Ok(true)
+	}
+
+	/// Runs cycles until the program halts.
+	///
+	/// # Errors
+	///
+	/// Fails if the program faults or doesn't follow the protocol.
+	pub fn run(&mut self, panels: &mut Panels) -> Result<(), RobotError> {
+		while self.step(panels)? {}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Heading, Panels, Robot, RobotError};
+	use crate::intcode::{asm::assemble, Intcode};
+
+	fn robot(source: &str) -> Robot {
+		Robot::new(Intcode::from(assemble(source).unwrap()))
+	}
+
+	#[test]
+	fn headings() {
+		assert_eq!(Heading::Up.turn_left().turn_left(), Heading::Down);
+		assert_eq!(Heading::Left.turn_right(), Heading::Up);
+		assert_eq!(Heading::Right.advance((0, 0)), (1, 0));
+		assert_eq!(Heading::Up.advance((0, 0)), (0, -1));
+	}
+
+	#[test]
+	fn paints_a_square() {
+		// Paints each panel the opposite of what it sees and turns right, four times.
+		let source = "
+			loop: in   [seen]
+			      eq   [seen], #0, [paint]
+			      out  [paint]
+			      out  #1
+			      add  [count], #-1, [count]
+			      jnz  [count], #loop
+			      hlt
+			seen:  data 0
+			paint: data 0
+			count: data 4
+		";
+
+		let mut panels = Panels::from([((1, 0), 1)]);
+		let mut robot = robot(source);
+		robot.run(&mut panels).unwrap();
+
+		assert_eq!(robot.position(), (0, 0));
+		assert_eq!(robot.heading(), Heading::Up);
+		assert_eq!(
+			panels,
+			Panels::from([((0, 0), 1), ((1, 0), 0), ((1, 1), 1), ((0, 1), 1)])
+		);
+	}
+
+	#[test]
+	fn protocol_errors() {
+		let mut panels = Panels::new();
+
+		assert_eq!(
+			robot("out #1\nhlt").run(&mut panels),
+			Err(RobotError::Unfinished)
+		);
+		assert_eq!(
+			robot("out #1\nout #2\nhlt").run(&mut panels),
+			Err(RobotError::UnknownTurn(2))
+		);
+		assert_eq!(
+			robot("in [0]\nin [0]\nhlt").run(&mut panels),
+			Err(RobotError::Stalled)
+		);
+	}
+}
@@ -1 +1,6 @@
-daocutil::generate_main!(d2019);
+daocutil::generate_main!(
+	d2019;
+	"disasm" => d2019::intcode::disasm::command,
+	"--debug" => d2019::intcode::debug::command,
+	"ascii" => d2019::intcode::ascii::command,
+);
@@ -1,23 +1,20 @@
-use regex::Regex;
+use daocutil::parse::ParseError;
 
 pub fn process_color(color: &str) -> String {
 	color.replace(' ', "_")
 }
 
-pub fn process_content_spec(content_spec: &str) -> (usize, String) {
-	let content_inner_spec = Regex::new("(\\d+) (.+) bags?").unwrap();
+pub fn process_content_spec(content_spec: &str) -> Result<(usize, String), ParseError> {
+	// "1 shiny gold bag", but "2 muted yellow bags".
+	let (number, color) = daocutil::scan!(content_spec, "{} {} bags", usize, String)
+		.or_else(|_| daocutil::scan!(content_spec, "{} {} bag", usize, String))?;
 
-	let captures = content_inner_spec.captures(content_spec).unwrap();
-
-	let number = captures.get(1).unwrap().as_str().parse::<usize>().unwrap();
-	let color = process_color(captures.get(2).unwrap().as_str());
-
-	(number, color)
+	Ok((number, process_color(&color)))
 }
 
-pub fn process_contents(contents: &str) -> Vec<(usize, String)> {
+pub fn process_contents(contents: &str) -> Result<Vec<(usize, String)>, ParseError> {
 	match contents {
-		"no other bags" => Vec::new(),
+		"no other bags" => Ok(Vec::new()),
 		_ => contents.split(", ").map(process_content_spec).collect(),
 	}
 }
@@ -35,15 +32,17 @@ pub fn ruleify(container: &str, contents: &[(usize, String)]) -> String {
 }
 
 pub fn generate_output(data: &str) -> Result<(), Box<dyn std::error::Error>> {
-	let bag_re = Regex::new("(.+) bags contain (.*).").unwrap();
-
-	for line in data.lines() {
-		let caps = bag_re.captures(line).unwrap();
+	let rules = daocutil::parse::lines(data, |line| {
+		let (color, contents) = daocutil::scan!(line, "{} bags contain {}.", String, String)?;
 
-		let color = process_color(caps.get(1).unwrap().as_str());
-		let content = process_contents(caps.get(2).unwrap().as_str());
+		Ok(ruleify(
+			&process_color(&color),
+			&process_contents(&contents)?,
+		))
+	})?;
 
-		println!("{}", ruleify(&color, &content));
+	for rule in rules {
+		println!("{rule}");
 	}
 
 	println!(
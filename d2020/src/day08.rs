@@ -1,6 +1,8 @@
 use core::str::FromStr;
 use std::collections::VecDeque;
 
+use daocutil::parse::ParseError;
+
 #[derive(Debug, Clone, Copy)]
 pub enum Instruction {
 	Acc(isize),
@@ -9,16 +11,16 @@ pub enum Instruction {
 }
 
 impl FromStr for Instruction {
-	type Err = ();
+	type Err = ParseError;
 
-	fn from_str(s: &str) -> Result<Self, ()> {
-		let parameter: isize = s.split(' ').nth(1).unwrap().parse::<isize>().unwrap();
+	fn from_str(s: &str) -> Result<Self, ParseError> {
+		let (operation, parameter) = daocutil::scan!(s, "{} {}", String, isize)?;
 
-		match &s[0..3] {
+		match operation.as_str() {
 			"nop" => Ok(Instruction::Nop(parameter)),
 			"acc" => Ok(Instruction::Acc(parameter)),
 			"jmp" => Ok(Instruction::Jmp(parameter)),
-			_ => todo!(),
+			_ => Err(ParseError::invalid(1, &operation, "unknown operation")),
 		}
 	}
 }
@@ -74,13 +76,8 @@ pub fn execute_program(program: &VecDeque<Instruction>) -> ExecutionResult<isize
 pub type Intermediate = VecDeque<Instruction>;
 pub type Solution = isize;
 
-pub fn parse(data: &str) -> Result<Intermediate, core::convert::Infallible> {
-	Ok(
-		data
-			.lines()
-			.map(|ln| ln.parse().expect("failed to parse instruction"))
-			.collect(),
-	)
+pub fn parse(data: &str) -> Result<Intermediate, ParseError> {
+	Ok(daocutil::parse::lines(data, str::parse)?.into())
 }
 
 pub fn part_one(instructions: &Intermediate) -> Option<Solution> {
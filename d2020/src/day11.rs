@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use daocutil::automaton::{moore, Automaton, Dense};
+
 pub type Intermediate = Layout;
 pub type Solution = usize;
 
@@ -8,66 +10,51 @@ pub fn parse(input: &str) -> Result<Intermediate, LayoutParseError> {
 }
 
 pub fn part_one(intermediate: &Intermediate) -> Option<Solution> {
-	let mut layout = intermediate.clone();
-
-	let mut prev_layout = None;
-
-	while prev_layout.is_none() || layout != prev_layout.unwrap() {
-		prev_layout = Some(layout.clone());
-		layout = layout.advance(transition, Layout::occupied_neighbors, 4_usize);
-	}
-
-	Some(
-		layout
-			.cells
-			.values()
-			.filter(|cell| **cell == Occupied)
-			.count(),
-	)
+	Some(settle(intermediate.seats(), moore, 4_usize))
 }
 
 pub fn part_two(intermediate: &Intermediate) -> Option<Solution> {
-	let mut layout = intermediate.clone();
+	let visible = intermediate.visible_seats();
 
-	let mut prev_layout = None;
-
-	while prev_layout.is_none() || layout != prev_layout.unwrap() {
-		prev_layout = Some(layout.clone());
-		layout = layout.advance(transition, Layout::visible_occupied_neighbors, 5_usize);
-	}
-
-	Some(
-		layout
-			.cells
-			.values()
-			.filter(|cell| **cell == Occupied)
-			.count(),
-	)
+	Some(settle(
+		intermediate.seats(),
+		|pos| visible.cell(pos).into_iter().flatten().copied(),
+		5_usize,
+	))
 }
 
-fn transition<F>(
-	layout: &Layout,
-	coords: Coords,
-	cell: &CellState,
-	occupied_neighbors_fn: F,
-	crowding_threshold: usize,
-) -> CellState
+fn settle<N, I>(seats: Dense<CellState>, neighborhood: N, crowding_threshold: usize) -> usize
 where
-	F: Fn(&Layout, Coords) -> usize,
+	N: Fn((i32, i32)) -> I,
+	I: IntoIterator<Item = (i32, i32)>,
 {
-	let neighbors = occupied_neighbors_fn(layout, coords);
+	let mut automaton = Automaton::new(seats, neighborhood, |cell, neighbors| {
+		transition(cell, neighbors, crowding_threshold)
+	});
+
+	automaton.step_until_stable();
+
+	automaton
+		.grid()
+		.cells()
+		.filter(|cell| **cell == Occupied)
+		.count()
+}
+
+fn transition(cell: &CellState, neighbors: &[CellState], crowding_threshold: usize) -> CellState {
+	let occupied_neighbors = neighbors.iter().filter(|cell| **cell == Occupied).count();
 
 	match cell {
 		Floor => Floor,
 		Empty => {
-			if neighbors == 0 {
+			if occupied_neighbors == 0 {
 				Occupied
 			} else {
 				Empty
 			}
 		}
 		Occupied => {
-			if neighbors >= crowding_threshold {
+			if occupied_neighbors >= crowding_threshold {
 				Empty
 			} else {
 				Occupied
@@ -76,8 +63,9 @@ where
 	}
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
 enum CellState {
+	#[default]
 	Floor,
 	Empty,
 	Occupied,
@@ -143,76 +131,33 @@ impl core::str::FromStr for Layout {
 }
 
 impl Layout {
-	fn new(width: usize, height: usize) -> Layout {
-		Layout {
-			width,
-			height,
-			cells: HashMap::new(),
-		}
-	}
-
-	fn advance<TF, ONF>(
-		&self,
-		transition: TF,
-		occupied_neighbors_fn: ONF,
-		crowding_threshold: usize,
-	) -> Layout
-	where
-		TF: Fn(&Layout, Coords, &CellState, ONF, usize) -> CellState,
-		ONF: Fn(&Layout, Coords) -> usize + Copy,
-	{
-		let mut new_layout = Layout::new(self.width, self.height);
-
-		for row_idx in 0..self.height {
-			for col_idx in 0..self.width {
-				let coords = (row_idx, col_idx);
-				let cell = self.cells.get(&coords).unwrap();
-
-				let new_cell = transition(
-					self,
-					coords,
-					cell,
-					occupied_neighbors_fn,
-					crowding_threshold,
-				);
-				new_layout.cells.insert(coords, new_cell);
-			}
-		}
-
-		new_layout
-	}
-
-	fn occupied_neighbors(&self, coords: Coords) -> usize {
-		DELTAS.iter().fold(0, |count, &delta| {
-			if let Some(visible_coords) = self.delta_n(coords, delta, 1) {
-				if let Some(Occupied) = self.cells.get(&visible_coords) {
-					count + 1
-				} else {
-					count
-				}
-			} else {
-				count
-			}
+	/// Converts the layout into a grid, indexed by `(x, y)` rather than `(row, col)`.
+	fn seats(&self) -> Dense<CellState> {
+		Dense::from_fn(self.width, self.height, |(x, y)| {
+			let coords = (usize::try_from(y).unwrap(), usize::try_from(x).unwrap());
+			self.cells.get(&coords).unwrap().clone()
 		})
 	}
 
-	fn visible_occupied_neighbors(&self, coords: Coords) -> usize {
-		DELTAS.iter().fold(0, |count, &delta| {
-			if let Some(true) = (1..).find_map(|n| {
-				if let Some(visible_coords) = self.delta_n(coords, delta, n) {
-					match self.cells.get(&visible_coords) {
-						Some(Occupied) => Some(true),
-						Some(Empty) | None => Some(false),
-						_ => None,
-					}
-				} else {
-					Some(false)
-				}
-			}) {
-				count + 1
-			} else {
-				count
-			}
+	/// For each cell, the first seat visible in each direction, skipping over any floor.
+	fn visible_seats(&self) -> Dense<Vec<(i32, i32)>> {
+		Dense::from_fn(self.width, self.height, |(x, y)| {
+			let coords = (usize::try_from(y).unwrap(), usize::try_from(x).unwrap());
+
+			DELTAS
+				.iter()
+				.filter_map(|&delta| {
+					(1..)
+						.map_while(|n| self.delta_n(coords, delta, n))
+						.find(|visible_coords| self.cells.get(visible_coords) != Some(&Floor))
+				})
+				.map(|(row_idx, col_idx)| {
+					(
+						i32::try_from(col_idx).unwrap(),
+						i32::try_from(row_idx).unwrap(),
+					)
+				})
+				.collect()
 		})
 	}
 
@@ -273,3 +218,50 @@ fn layout_parse() {
 		})
 	);
 }
+
+#[cfg(test)]
+mod cycle {
+	use daocutil::{
+		automaton::{moore, Grid},
+		cycle::{brent, floyd, history, Cycle},
+	};
+
+	use super::{transition, Layout, Occupied};
+
+	const EXAMPLE: &str = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+	#[test]
+	fn example_settles_after_five_rounds() {
+		let seats = EXAMPLE.parse::<Layout>().unwrap().seats();
+		let round = |seats: &_| {
+			Grid::step(seats, &moore, &|cell, neighbors| {
+				transition(cell, neighbors, 4)
+			})
+		};
+
+		let settled = Cycle { mu: 5, lambda: 1 };
+
+		assert_eq!(floyd(seats.clone(), round), settled);
+		assert_eq!(brent(seats.clone(), round), settled);
+
+		let history = history(seats, round);
+		assert_eq!(history.cycle(), settled);
+		assert_eq!(
+			history
+				.state_at(1_000_000_000)
+				.cells()
+				.filter(|cell| **cell == Occupied)
+				.count(),
+			37
+		);
+	}
+}
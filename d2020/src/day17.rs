@@ -1,4 +1,6 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+
+use daocutil::automaton::{Automaton, Sparse};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Pos(i8, i8, i8, i8);
@@ -83,47 +85,39 @@ static NEIGHBOR_D: [(i8, i8, i8, i8); 80] = [
 	( 1,  1, -1,  1), ( 1,  1,  0,  1), ( 1,  1,  1,  1),
 ];
 
-fn count_neighbors_3d(active: &HashSet<Pos>) -> HashMap<Pos, usize> {
-	let mut neighbors = HashMap::new();
-	for Pos(x, y, z, _) in active {
-		for (dx, dy, dz, _dw) in &NEIGHBOR_D[26..52] {
-			*neighbors.entry(Pos(x + dx, y + dy, z + dz, 0)).or_insert(0) += 1;
-		}
-	}
-	neighbors
+fn neighbors_3d(Pos(x, y, z, _): Pos) -> impl Iterator<Item = Pos> {
+	NEIGHBOR_D[26..52]
+		.iter()
+		.map(move |(dx, dy, dz, _dw)| Pos(x + dx, y + dy, z + dz, 0))
 }
 
-fn count_neighbors_4d(active: &HashSet<Pos>) -> HashMap<Pos, usize> {
-	let mut neighbors = HashMap::new();
-	for Pos(x, y, z, w) in active {
-		for (dx, dy, dz, dw) in &NEIGHBOR_D {
-			*neighbors
-				.entry(Pos(x + dx, y + dy, z + dz, w + dw))
-				.or_insert(0) += 1;
-		}
-	}
-	neighbors
+fn neighbors_4d(Pos(x, y, z, w): Pos) -> impl Iterator<Item = Pos> {
+	NEIGHBOR_D
+		.iter()
+		.map(move |(dx, dy, dz, dw)| Pos(x + dx, y + dy, z + dz, w + dw))
 }
 
-fn process<CountingFn>(mut active: HashSet<Pos>, counting_fn: CountingFn) -> usize
+fn process<N, I>(active: HashSet<Pos>, neighborhood: N) -> usize
 where
-	CountingFn: Fn(&HashSet<Pos>) -> HashMap<Pos, usize>,
+	N: Fn(Pos) -> I,
+	I: IntoIterator<Item = Pos>,
 {
-	for _ in 0..6 {
-		active = counting_fn(&active)
-			.iter()
-			.filter(|&(pos, &n)| n == 3 || (n == 2 && active.contains(pos)))
-			.map(|(&pos, _)| pos)
-			.collect();
-	}
-
-	active.len()
+	let mut automaton = Automaton::new(
+		active.into_iter().collect::<Sparse<Pos>>(),
+		neighborhood,
+		|&active: &bool, neighbors: &[bool]| {
+			let n = neighbors.iter().filter(|&&neighbor| neighbor).count();
+			n == 3 || (n == 2 && active)
+		},
+	);
+
+	automaton.step_n(6).len()
 }
 
 pub fn part_one(active_cells: &Intermediate) -> Option<Solution> {
 	let active: HashSet<Pos> = active_cells.clone();
 
-	let count = process(active, count_neighbors_3d);
+	let count = process(active, neighbors_3d);
 
 	Some(count as u64)
 }
@@ -143,7 +137,7 @@ mod part_one {
 pub fn part_two(active_cells: &Intermediate) -> Option<Solution> {
 	let active: HashSet<Pos> = active_cells.clone();
 
-	let count = process(active, count_neighbors_4d);
+	let count = process(active, neighbors_4d);
 
 	Some(count as u64)
 }
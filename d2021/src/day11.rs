@@ -8,15 +8,22 @@ mod neighbors;
 mod state;
 use state::State;
 
+use daocutil::viz::Animation;
+
 type Solution = usize;
 
 #[must_use] pub fn part_one(state: &Intermediate) -> Option<Solution> {
 	let mut state: State<10> = state.clone();
 
 	let mut total_flashes: usize = 0;
+	let mut animation = Animation::from_flag();
 
 	for _ in 0..100 {
 		total_flashes += state.tick();
+
+		if let Some(animation) = &mut animation {
+			animation.frame(&state.frame());
+		}
 	}
 
 	Some(total_flashes)
@@ -25,11 +32,16 @@ type Solution = usize;
 #[must_use] pub fn part_two(state: &Intermediate) -> Option<Solution> {
 	let mut state: State<10> = state.clone();
 	let mut step_counter: usize = 0;
+	let mut animation = Animation::from_flag();
 
 	loop {
 		let flashes = state.tick();
 		step_counter += 1;
 
+		if let Some(animation) = &mut animation {
+			animation.frame(&state.frame());
+		}
+
 		if flashes == 10_usize * 10_usize {
 			break;
 		}
@@ -1,3 +1,5 @@
+use daocutil::viz::{self, Color, Glyph};
+
 use super::neighbors::neighbors;
 
 #[derive(Clone, Debug)]
@@ -68,6 +70,21 @@ impl<const N: usize> core::str::FromStr for State<N> {
 }
 
 impl<const N: usize> State<N> {
+	/// A rendering of the energy levels for animating, in which octopi that just flashed light up.
+	pub fn frame(&self) -> String {
+		viz::render_rows(self.octopi.iter().map(|row| {
+			row.iter().map(|Octopus(EnergyLevel(level))| {
+				let symbol = char::from_digit(u32::from(*level), 10).unwrap();
+
+				if *level == 0 {
+					Glyph::new(symbol, Color::Yellow.bold())
+				} else {
+					Glyph::colored(symbol, Color::Fixed(236 + 2 * level))
+				}
+			})
+		}))
+	}
+
 	pub fn tick(&mut self) -> usize {
 		use std::collections::VecDeque;
 
@@ -2,14 +2,19 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 
-#[derive(PartialEq, Clone, Copy)]
-enum Direction {
+use daocutil::{
+	automaton::{moore_with_center, Automaton, Grid},
+	viz::{self, Animation, Color, Glyph, Style},
+};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Direction {
 	East,
 	South,
 }
 
 #[derive(thiserror::Error, Debug)]
-enum DirectionParseError {
+pub enum DirectionParseError {
 	#[error("unrecognized character `{0}`")]
 	UnrecognizedChar(char),
 }
@@ -25,7 +30,7 @@ impl TryFrom<char> for Direction {
 	}
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Space<const WIDTH: usize, const HEIGHT: usize> {
 	contents: [[Option<Direction>; WIDTH]; HEIGHT],
 }
@@ -81,62 +86,76 @@ impl<const WIDTH: usize, const HEIGHT: usize> core::str::FromStr for Space<WIDTH
 }
 
 impl<const WIDTH: usize, const HEIGHT: usize> Space<WIDTH, HEIGHT> {
-	fn next_east(x: usize) -> usize {
-		let mut x = x;
-
-		x += 1;
-
-		while x >= WIDTH {
-			x -= WIDTH;
-		}
-
-		x
+	/// A colored rendering of the sea floor, for animating.
+	fn frame(&self) -> String {
+		viz::render_rows(self.contents.iter().map(|row| {
+			row.iter().map(|cell| match cell {
+				Some(Direction::East) => Glyph::colored('>', Color::Cyan),
+				Some(Direction::South) => Glyph::colored('v', Color::Yellow),
+				None => Glyph::new('.', Style::new().dimmed()),
+			})
+		}))
 	}
+}
 
-	fn next_south(y: usize) -> usize {
-		let mut y = y;
-
-		y += 1;
+/// The sea floor wraps around, so every position maps onto some cell.
+impl<const WIDTH: usize, const HEIGHT: usize> Grid for Space<WIDTH, HEIGHT> {
+	type Pos = (i32, i32);
+	type Cell = Option<Direction>;
 
-		while y >= HEIGHT {
-			y -= HEIGHT;
-		}
+	fn get(&self, (x, y): Self::Pos) -> Option<Self::Cell> {
+		let x = usize::try_from(x.rem_euclid(i32::try_from(WIDTH).unwrap())).unwrap();
+		let y = usize::try_from(y.rem_euclid(i32::try_from(HEIGHT).unwrap())).unwrap();
 
-		y
+		Some(self.contents[y][x])
 	}
 
-	fn iterate(self) -> (Self, usize) {
+	fn step<N, I, R>(&self, neighborhood: &N, rule: &R) -> Self
+	where
+		N: Fn(Self::Pos) -> I,
+		I: IntoIterator<Item = Self::Pos>,
+		R: Fn(&Self::Cell, &[Self::Cell]) -> Self::Cell,
+	{
 		let mut contents = self.contents;
+		let mut neighbors = Vec::new();
 
-		let mut moves = 0;
-
-		//println!("{}", self);
-
-		let leading_east_edge: Vec<(usize, usize)> = (0..HEIGHT)
-			.flat_map(|y| (0..WIDTH).map(move |x| (WIDTH - 1 - x, y)))
-			.filter(|(x, y)| contents[*y][*x] == Some(Direction::East))
-			.filter(|&(x, y)| contents[y][Self::next_east(x)].is_none())
-			.collect();
-
-		for (x, y) in leading_east_edge {
-			contents[y][Self::next_east(x)] = contents[y][x];
-			contents[y][x] = None;
-			moves += 1;
+		for (y, row) in contents.iter_mut().enumerate() {
+			for (x, cell) in row.iter_mut().enumerate() {
+				let pos = (i32::try_from(x).unwrap(), i32::try_from(y).unwrap());
+				self.neighbors_into(pos, neighborhood, &mut neighbors);
+				*cell = rule(&self.contents[y][x], &neighbors);
+			}
 		}
 
-		let leading_south_edge: Vec<(usize, usize)> = (0..WIDTH)
-			.flat_map(|x| (0..HEIGHT).map(move |y| (x, HEIGHT - 1 - y)))
-			.filter(|(x, y)| contents[*y][*x] == Some(Direction::South))
-			.filter(|&(x, y)| contents[Self::next_south(y)][x].is_none())
-			.collect();
+		Self { contents }
+	}
+}
 
-		for (x, y) in leading_south_edge {
-			contents[Self::next_south(y)][x] = contents[y][x];
-			contents[y][x] = None;
-			moves += 1;
-		}
+/// Where the cucumber at the center of a west-center-east row ends up after the east-facing herd
+/// moves.
+fn after_east_move(
+	west: Option<Direction>,
+	center: Option<Direction>,
+	east: Option<Direction>,
+) -> Option<Direction> {
+	match (west, center, east) {
+		(Some(Direction::East), None, _) => Some(Direction::East),
+		(_, Some(Direction::East), None) => None,
+		(_, center, _) => center,
+	}
+}
 
-		(Self { contents }, moves)
+/// Advances the center of a 3x3 block (in reading order) by one full step: first the east-facing
+/// herd moves, then the south-facing herd.
+fn step(block: &[Option<Direction>]) -> Option<Direction> {
+	let north = after_east_move(block[0], block[1], block[2]);
+	let center = after_east_move(block[3], block[4], block[5]);
+	let south = after_east_move(block[6], block[7], block[8]);
+
+	match (north, center, south) {
+		(Some(Direction::South), None, _) => Some(Direction::South),
+		(_, Some(Direction::South), None) => None,
+		(_, center, _) => center,
 	}
 }
 
@@ -150,26 +169,59 @@ type Solution = usize;
 
 #[must_use]
 pub fn part_one(floor: &Intermediate) -> Option<Solution> {
-	let mut space: Intermediate = (*floor);
-
-	let mut counter = 0;
-
-	loop {
-		let (new_space, count) = space.iterate();
+	let mut automaton = Automaton::new(*floor, moore_with_center, |_, block| step(block));
 
-		counter += 1;
-
-		if count == 0 {
-			break;
+	Some(match Animation::from_flag() {
+		Some(mut animation) => {
+			automaton.step_until_stable_with(|space| animation.frame(&space.frame()))
 		}
-
-		space = new_space;
-	}
-
-	Some(counter)
+		None => automaton.step_until_stable(),
+	})
 }
 
 #[must_use]
 pub fn part_two(_intermediate: &Intermediate) -> Option<Solution> {
 	None
 }
+
+#[cfg(test)]
+mod cycle {
+	use daocutil::{
+		automaton::{moore_with_center, Grid},
+		cycle::{brent, floyd, history, state_at, Cycle},
+	};
+
+	use super::{step, Space};
+
+	const EXAMPLE: &str = "v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>";
+
+	fn advance(space: &Space<10, 9>) -> Space<10, 9> {
+		space.step(&moore_with_center, &|_, block| step(block))
+	}
+
+	#[test]
+	fn example_stops_moving_on_step_58() {
+		let space: Space<10, 9> = EXAMPLE.parse().unwrap();
+
+		// Step 58 is the first one where nothing moves, so states 57 and 58 are the same.
+		let stopped = Cycle { mu: 57, lambda: 1 };
+
+		assert_eq!(floyd(space, advance), stopped);
+		assert_eq!(brent(space, advance), stopped);
+
+		let history = history(space, advance);
+		assert_eq!(history.cycle(), stopped);
+		assert_eq!(
+			state_at(space, advance, stopped, 1_000_000_000),
+			*history.state_at(57)
+		);
+	}
+}
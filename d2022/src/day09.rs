@@ -7,6 +7,8 @@ use core::{
 
 use std::collections::BTreeMap;
 
+use daocutil::viz::{self, Animation, Color, Glyph, Style};
+
 pub type Intermediate = Vec<Move>;
 pub type Output = usize;
 
@@ -16,77 +18,58 @@ pub struct State {
 	tail_history: BTreeMap<(i32, i32), usize>,
 }
 
-#[derive(Default)]
-struct StateDisplay {
-	min: (i32, i32),
-	max: (i32, i32),
-	symbols: BTreeMap<(i32, i32), String>,
-}
-
-impl StateDisplay {
-	fn add_symbol(&mut self, position: (i32, i32), symbol: String) {
-		if position.0 - 1 < self.min.0 {
-			self.min.0 = position.0 - 1;
-		}
-		if position.1 - 1 < self.min.1 {
-			self.min.1 = position.1 - 1;
-		}
-		if position.0 + 1 > self.max.0 {
-			self.max.0 = position.0 + 1;
-		}
-		if position.1 + 1 > self.max.1 {
-			self.max.1 = position.1 + 1;
-		}
-
-		self.symbols.insert(position, symbol);
-	}
-}
-
-impl Display for StateDisplay {
-	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-		debug_assert!(self.min.0 < self.max.0);
-		debug_assert!(self.min.1 < self.max.1);
-
-		for y in self.min.1..=self.max.1 {
-			for x in self.min.0..=self.max.0 {
-				let sym = self.symbols.get(&(x, y));
-				match sym {
-					Some(s) => write!(f, "{s}"),
-					None => write!(f, " "),
-				}?;
-			}
-			writeln!(f)?;
-		}
-		Ok(())
-	}
-}
-
-impl Display for State {
-	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-		let mut display = StateDisplay::default();
-
+impl State {
+	/// Everything worth drawing, from the bottom layer up.
+	fn glyphs(&self) -> Vec<((i32, i32), Glyph)> {
 		// seen tail positions are always on the bottom
-		for pos in self.tail_history.keys() {
-			display.add_symbol(*pos, "*".to_string());
-		}
+		let mut glyphs: Vec<((i32, i32), Glyph)> = self
+			.tail_history
+			.keys()
+			.map(|pos| (*pos, Glyph::new('*', Style::new().dimmed())))
+			.collect();
 
 		// start is next
-		display.add_symbol(self.start, "s".to_string());
+		glyphs.push((self.start, Glyph::colored('s', Color::Blue)));
 
 		// rope positions are next...
 		for (idx, pos) in self.rope.positions().enumerate() {
-			display.add_symbol(*pos, (idx % 10).to_string());
+			let symbol = char::from_digit(u32::try_from(idx % 10).unwrap(), 10).unwrap();
+			glyphs.push((*pos, Glyph::colored(symbol, Color::Yellow)));
 		}
 
 		// tail after that
-		display.add_symbol(*self.rope.tail(), "T".to_string());
+		glyphs.push((*self.rope.tail(), Glyph::colored('T', Color::Green)));
 
 		// head is always last, therefore on top.
-		display.add_symbol(*self.rope.head(), "H".to_string());
+		glyphs.push((*self.rope.head(), Glyph::new('H', Color::Red.bold())));
+
+		// a blank border one cell wide goes underneath everything
+		let (min, max) = glyphs.iter().fold(
+			(*self.rope.head(), *self.rope.head()),
+			|(min, max), &((x, y), _)| ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y))),
+		);
+		glyphs.splice(
+			0..0,
+			[(min.0 - 1, min.1 - 1), (max.0 + 1, max.1 + 1)].map(|pos| (pos, ' '.into())),
+		);
+
+		glyphs
+	}
+
+	/// A colored rendering of the state, for animating.
+	fn frame(&self) -> String {
+		viz::render_sparse(self.glyphs(), ' '.into())
+	}
+}
 
-		writeln!(f, "{display}")
+impl Display for State {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let glyphs = self
+			.glyphs()
+			.into_iter()
+			.map(|(pos, glyph)| (pos, glyph.symbol.into()));
 
-		// todo!()
+		writeln!(f, "{}", viz::render_sparse(glyphs, ' '.into()))
 	}
 }
 
@@ -246,14 +229,14 @@ pub fn part_two(moves: &Intermediate) -> Option<Output> {
 		tail_history: BTreeMap::default(),
 	};
 
-	// println!("== Initial State ==");
-
-	// print!("{}", state);
+	let mut animation = Animation::from_flag();
 
 	for r#move in moves {
-		// println!("== {move} ==");
 		state.apply_move(r#move);
-		// print!("{}", state);
+
+		if let Some(animation) = &mut animation {
+			animation.frame(&state.frame());
+		}
 	}
 
 	Some(state.tail_history.len())
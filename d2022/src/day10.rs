@@ -1,23 +1,25 @@
 use core::{
 	array::TryFromSliceError,
 	fmt::{self, Debug, Display, Formatter},
-	num::ParseIntError,
 	str::FromStr,
 };
 
+use daocutil::parse::ParseError;
+
 pub enum Instruction {
 	Noop,
 	Addx(i32),
 }
 
 impl FromStr for Instruction {
-	type Err = ParseIntError;
+	type Err = ParseError;
 
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		match &s[0..4] {
-			"noop" => Ok(Self::Noop),
-			"addx" => s[5..].parse().map(Self::Addx),
-			_ => unreachable!(),
+		if s == "noop" {
+			Ok(Self::Noop)
+		} else {
+			let (v,) = daocutil::scan!(s, "addx {}", i32)?;
+			Ok(Self::Addx(v))
 		}
 	}
 }
@@ -54,12 +56,7 @@ impl Debug for Output {
 
 /// # Errors
 pub fn parse(input: &str) -> anyhow::Result<Intermediate> {
-	Ok(
-		input
-			.lines()
-			.map(str::parse)
-			.collect::<Result<Vec<_>, _>>()?,
-	)
+	Ok(daocutil::parse::lines(input, str::parse)?)
 }
 
 fn values_during_and_after(instructions: &[Instruction]) -> (Vec<i32>, Vec<i32>) {
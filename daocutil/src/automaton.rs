@@ -0,0 +1,662 @@
+//! A small cellular-automaton engine.
+//!
+//! An [`Automaton`] pairs a [`Grid`] with a *neighborhood* (a function from a position to the
+//! positions it looks at) and a *rule* (a function from a cell and its neighbors' cells to the
+//! cell's next value).  Puzzles then only need to describe their rule; stepping, running until
+//! nothing changes, and finding repeated states are handled here.
+
+use std::{
+	collections::{HashMap, HashSet},
+	hash::{DefaultHasher, Hash, Hasher},
+};
+
+use crate::{
+	cycle::Cycle,
+	neighbors::{OFFSETS_EXCLUDING_DIAGS, OFFSETS_INCLUDING_DIAGS},
+};
+
+/// A collection of cells which can be advanced by one generation.
+pub trait Grid: Clone + PartialEq {
+	type Pos: Copy;
+	type Cell: Clone;
+
+	/// Returns the cell at `pos`, or `None` if `pos` lies outside of the grid.  Every grid in this
+	/// module has a cell everywhere, so only returns `None` for grids defined elsewhere.
+	fn get(&self, pos: Self::Pos) -> Option<Self::Cell>;
+
+	/// Produces the next generation by applying `rule` at every position whose cell could change.
+	#[must_use]
+	fn step<N, I, R>(&self, neighborhood: &N, rule: &R) -> Self
+	where
+		N: Fn(Self::Pos) -> I,
+		I: IntoIterator<Item = Self::Pos>,
+		R: Fn(&Self::Cell, &[Self::Cell]) -> Self::Cell;
+
+	/// Replaces the contents of `cells` with the cells neighboring `pos`, in the order the
+	/// neighborhood yields them.  Positions for which [`Grid::get`] gives `None` are skipped, so
+	/// rules which pick out neighbors by index (like the center of [`moore_with_center`]) should
+	/// only be used on grids which have a cell everywhere.
+	fn neighbors_into<N, I>(&self, pos: Self::Pos, neighborhood: &N, cells: &mut Vec<Self::Cell>)
+	where
+		N: Fn(Self::Pos) -> I,
+		I: IntoIterator<Item = Self::Pos>,
+	{
+		cells.clear();
+		cells.extend(
+			neighborhood(pos)
+				.into_iter()
+				.filter_map(|pos| self.get(pos)),
+		);
+	}
+}
+
+/// The eight cells surrounding `(x, y)`.
+pub fn moore((x, y): (i32, i32)) -> [(i32, i32); 8] {
+	OFFSETS_INCLUDING_DIAGS.map(|(dx, dy)| (x + dx, y + dy))
+}
+
+/// The 3x3 block centered on `(x, y)` in reading order, _including_ `(x, y)` itself.
+pub fn moore_with_center((x, y): (i32, i32)) -> [(i32, i32); 9] {
+	let mut block = [(0, 0); 9];
+
+	for (idx, pos) in block.iter_mut().enumerate() {
+		let (dx, dy) = (idx % 3, idx / 3);
+		*pos = (
+			x + i32::try_from(dx).unwrap() - 1,
+			y + i32::try_from(dy).unwrap() - 1,
+		);
+	}
+
+	block
+}
+
+/// The four cells orthogonally adjacent to `(x, y)`.
+pub fn von_neumann((x, y): (i32, i32)) -> [(i32, i32); 4] {
+	OFFSETS_EXCLUDING_DIAGS.map(|(dx, dy)| (x + dx, y + dy))
+}
+
+/// A bounded, rectangular grid.  When stepping, cells outside of the bounds read as
+/// `T::default()`, so every neighborhood is seen in full; the default should behave like
+/// whatever surrounds the grid, such as a dead cell or empty floor.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Dense<T> {
+	width: usize,
+	height: usize,
+	cells: Vec<T>,
+}
+
+impl<T> Dense<T> {
+	/// A grid with no cells at all.
+	pub fn empty() -> Self {
+		Self {
+			width: 0,
+			height: 0,
+			cells: Vec::new(),
+		}
+	}
+
+	/// Builds a grid by calling `f` with the `(x, y)` of every cell.
+	pub fn from_fn(width: usize, height: usize, mut f: impl FnMut((i32, i32)) -> T) -> Self {
+		let cells = (0..height)
+			.flat_map(|y| (0..width).map(move |x| (x, y)))
+			.map(|(x, y)| f((i32::try_from(x).unwrap(), i32::try_from(y).unwrap())))
+			.collect();
+
+		Self {
+			width,
+			height,
+			cells,
+		}
+	}
+
+	/// Builds a grid from its rows, returning `None` if the rows are not all the same length.
+	pub fn from_rows<Rows, Row>(rows: Rows) -> Option<Self>
+	where
+		Rows: IntoIterator<Item = Row>,
+		Row: IntoIterator<Item = T>,
+	{
+		let mut width = None;
+		let mut height = 0;
+		let mut cells = Vec::new();
+
+		for row in rows {
+			let before = cells.len();
+			cells.extend(row);
+
+			match width {
+				None => width = Some(cells.len() - before),
+				Some(width) if width != cells.len() - before => return None,
+				Some(_) => {}
+			}
+
+			height += 1;
+		}
+
+		Some(Self {
+			width: width.unwrap_or(0),
+			height,
+			cells,
+		})
+	}
+
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	fn index(&self, (x, y): (i32, i32)) -> Option<usize> {
+		let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+
+		if x < self.width && y < self.height {
+			Some(y * self.width + x)
+		} else {
+			None
+		}
+	}
+
+	/// Borrows the cell at `(x, y)`, if it is in bounds.
+	pub fn cell(&self, pos: (i32, i32)) -> Option<&T> {
+		self.index(pos).map(|idx| &self.cells[idx])
+	}
+
+	/// Iterates over every cell in reading order.
+	pub fn cells(&self) -> impl Iterator<Item = &T> {
+		self.cells.iter()
+	}
+
+	/// Iterates over every cell in reading order along with its `(x, y)`.
+	pub fn iter(&self) -> impl Iterator<Item = ((i32, i32), &T)> {
+		let width = self.width;
+
+		self.cells.iter().enumerate().map(move |(idx, cell)| {
+			(
+				(
+					i32::try_from(idx % width).unwrap(),
+					i32::try_from(idx / width).unwrap(),
+				),
+				cell,
+			)
+		})
+	}
+}
+
+impl<T: Clone + Default + PartialEq> Grid for Dense<T> {
+	type Pos = (i32, i32);
+	type Cell = T;
+
+	fn get(&self, pos: Self::Pos) -> Option<Self::Cell> {
+		Some(self.cell(pos).cloned().unwrap_or_default())
+	}
+
+	fn step<N, I, R>(&self, neighborhood: &N, rule: &R) -> Self
+	where
+		N: Fn(Self::Pos) -> I,
+		I: IntoIterator<Item = Self::Pos>,
+		R: Fn(&Self::Cell, &[Self::Cell]) -> Self::Cell,
+	{
+		let mut neighbors = Vec::new();
+
+		let cells = self
+			.iter()
+			.map(|(pos, cell)| {
+				self.neighbors_into(pos, neighborhood, &mut neighbors);
+				rule(cell, &neighbors)
+			})
+			.collect();
+
+		Self {
+			width: self.width,
+			height: self.height,
+			cells,
+		}
+	}
+}
+
+/// An unbounded grid of live (`true`) and dead (`false`) cells, storing only the live ones.
+///
+/// Only live cells and their neighbors are evaluated on each step, so a dead cell surrounded by
+/// dead cells must stay dead under the rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sparse<P: Eq + Hash> {
+	live: HashSet<P>,
+}
+
+/// `HashSet` has no `Hash` of its own since its iteration order varies, so the live cells' hashes
+/// are combined in a way which doesn't depend on order.
+impl<P: Eq + Hash> Hash for Sparse<P> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		let combined = self.live.iter().fold(0_u64, |combined, pos| {
+			let mut hasher = DefaultHasher::new();
+			pos.hash(&mut hasher);
+			combined.wrapping_add(hasher.finish())
+		});
+
+		self.live.len().hash(state);
+		combined.hash(state);
+	}
+}
+
+impl<P: Copy + Eq + Hash> Sparse<P> {
+	pub fn contains(&self, pos: &P) -> bool {
+		self.live.contains(pos)
+	}
+
+	pub fn len(&self) -> usize {
+		self.live.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.live.is_empty()
+	}
+
+	/// Iterates over the positions of the live cells, in no particular order.
+	pub fn iter(&self) -> impl Iterator<Item = &P> {
+		self.live.iter()
+	}
+}
+
+impl<P: Copy + Eq + Hash> FromIterator<P> for Sparse<P> {
+	fn from_iter<It: IntoIterator<Item = P>>(iter: It) -> Self {
+		Self {
+			live: iter.into_iter().collect(),
+		}
+	}
+}
+
+impl<P: Copy + Eq + Hash> Grid for Sparse<P> {
+	type Pos = P;
+	type Cell = bool;
+
+	fn get(&self, pos: Self::Pos) -> Option<Self::Cell> {
+		Some(self.live.contains(&pos))
+	}
+
+	fn step<N, I, R>(&self, neighborhood: &N, rule: &R) -> Self
+	where
+		N: Fn(Self::Pos) -> I,
+		I: IntoIterator<Item = Self::Pos>,
+		R: Fn(&Self::Cell, &[Self::Cell]) -> Self::Cell,
+	{
+		let candidates: HashSet<P> = self
+			.live
+			.iter()
+			.flat_map(|&pos| core::iter::once(pos).chain(neighborhood(pos)))
+			.collect();
+
+		let mut neighbors = Vec::new();
+
+		let live = candidates
+			.into_iter()
+			.filter(|&pos| {
+				self.neighbors_into(pos, neighborhood, &mut neighbors);
+				rule(&self.live.contains(&pos), &neighbors)
+			})
+			.collect();
+
+		Self { live }
+	}
+}
+
+/// An unbounded grid where every cell outside of a finite window holds the same `background`
+/// value.
+///
+/// Each step works out the window grown by `margin` cells on every side, then trims it back down
+/// to the cells which differ from the background, so that equal states always compare (and hash)
+/// equal.  The background itself is advanced by applying the rule to a background cell
+/// surrounded entirely by background.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Infinite<T> {
+	window: Dense<T>,
+	origin: (i32, i32),
+	background: T,
+	margin: usize,
+}
+
+impl<T> Infinite<T> {
+	/// Places `window` with its top-left corner at `(0, 0)` on a field of `background`.
+	pub fn new(window: Dense<T>, background: T) -> Self {
+		Self {
+			window,
+			origin: (0, 0),
+			background,
+			margin: 1,
+		}
+	}
+
+	/// Sets how far outside the window a neighborhood can reach.  Defaults to 1.
+	#[must_use]
+	pub fn with_margin(self, margin: usize) -> Self {
+		Self { margin, ..self }
+	}
+
+	pub fn background(&self) -> &T {
+		&self.background
+	}
+
+	/// The window of non-background cells, whose top-left corner is at [`Infinite::origin`].
+	pub fn window(&self) -> &Dense<T> {
+		&self.window
+	}
+
+	pub fn origin(&self) -> (i32, i32) {
+		self.origin
+	}
+}
+
+impl<T: Clone + PartialEq> Grid for Infinite<T> {
+	type Pos = (i32, i32);
+	type Cell = T;
+
+	fn get(&self, (x, y): Self::Pos) -> Option<Self::Cell> {
+		let inner = (x - self.origin.0, y - self.origin.1);
+
+		Some(self.window.cell(inner).unwrap_or(&self.background).clone())
+	}
+
+	fn step<N, I, R>(&self, neighborhood: &N, rule: &R) -> Self
+	where
+		N: Fn(Self::Pos) -> I,
+		I: IntoIterator<Item = Self::Pos>,
+		R: Fn(&Self::Cell, &[Self::Cell]) -> Self::Cell,
+	{
+		let margin = i32::try_from(self.margin).unwrap();
+		let origin = (self.origin.0 - margin, self.origin.1 - margin);
+
+		let mut neighbors = Vec::new();
+
+		let window = Dense::from_fn(
+			self.window.width + 2 * self.margin,
+			self.window.height + 2 * self.margin,
+			|(x, y)| {
+				let pos = (origin.0 + x, origin.1 + y);
+				self.neighbors_into(pos, neighborhood, &mut neighbors);
+				rule(&self.get(pos).unwrap(), &neighbors)
+			},
+		);
+
+		let neighbor_count = neighborhood(self.origin).into_iter().count();
+		let background = rule(
+			&self.background,
+			&vec![self.background.clone(); neighbor_count],
+		);
+
+		Self {
+			window,
+			origin,
+			background,
+			margin: self.margin,
+		}
+		.trimmed()
+	}
+}
+
+impl<T: Clone + PartialEq> Infinite<T> {
+	/// Shrinks the window to the smallest one holding every non-background cell.  An empty window
+	/// sits at `(0, 0)`.
+	fn trimmed(self) -> Self {
+		let bounds = self
+			.window
+			.iter()
+			.filter(|&(_, cell)| *cell != self.background)
+			.fold(None, |bounds, ((x, y), _)| {
+				Some(match bounds {
+					None => ((x, y), (x, y)),
+					Some(((x_min, y_min), (x_max, y_max))) => {
+						((x.min(x_min), y.min(y_min)), (x.max(x_max), y.max(y_max)))
+					}
+				})
+			});
+
+		let Some(((x_min, y_min), (x_max, y_max))) = bounds else {
+			return Self {
+				window: Dense::empty(),
+				origin: (0, 0),
+				..self
+			};
+		};
+
+		let window = Dense::from_fn(
+			usize::try_from(x_max - x_min + 1).unwrap(),
+			usize::try_from(y_max - y_min + 1).unwrap(),
+			|(x, y)| self.window.cell((x_min + x, y_min + y)).unwrap().clone(),
+		);
+
+		Self {
+			window,
+			origin: (self.origin.0 + x_min, self.origin.1 + y_min),
+			..self
+		}
+	}
+}
+
+/// A [`Grid`] together with the neighborhood and rule that advance it.
+pub struct Automaton<G, N, R> {
+	grid: G,
+	neighborhood: N,
+	rule: R,
+	generation: usize,
+}
+
+impl<G, N, I, R> Automaton<G, N, R>
+where
+	G: Grid,
+	N: Fn(G::Pos) -> I,
+	I: IntoIterator<Item = G::Pos>,
+	R: Fn(&G::Cell, &[G::Cell]) -> G::Cell,
+{
+	pub fn new(grid: G, neighborhood: N, rule: R) -> Self {
+		Self {
+			grid,
+			neighborhood,
+			rule,
+			generation: 0,
+		}
+	}
+
+	pub fn grid(&self) -> &G {
+		&self.grid
+	}
+
+	pub fn into_grid(self) -> G {
+		self.grid
+	}
+
+	/// The number of steps taken so far.
+	pub fn generation(&self) -> usize {
+		self.generation
+	}
+
+	/// Advances by a single generation, returning whether anything changed.
+	pub fn step(&mut self) -> bool {
+		let next = self.grid.step(&self.neighborhood, &self.rule);
+		let changed = next != self.grid;
+
+		self.grid = next;
+		self.generation += 1;
+
+		changed
+	}
+
+	/// Advances by `n` generations.  Once a step changes nothing, the remaining steps are skipped
+	/// since they could not change anything either.
+	pub fn step_n(&mut self, n: usize) -> &G {
+		for taken in 1..=n {
+			if !self.step() {
+				self.generation += n - taken;
+				break;
+			}
+		}
+
+		&self.grid
+	}
+
+	/// Steps until a step changes nothing, returning how many steps were taken (including that
+	/// final one).
+	pub fn step_until_stable(&mut self) -> usize {
+		self.step_until_stable_with(|_| {})
+	}
+
+	/// Like [`Automaton::step_until_stable`], but calls `inspect` with the grid after every step
+	/// that changed something, e.g. to draw it.
+	pub fn step_until_stable_with(&mut self, mut inspect: impl FnMut(&G)) -> usize {
+		let mut steps = 1;
+
+		while self.step() {
+			inspect(&self.grid);
+			steps += 1;
+		}
+
+		steps
+	}
+
+	/// Steps until the grid is in a state it has been in before, leaving it in that state.
+	///
+	/// See [`crate::cycle`] for finding cycles without keeping the automaton around.
+	pub fn find_cycle(&mut self) -> Cycle
+	where
+		G: Eq + Hash,
+	{
+		let mut seen: HashMap<G, usize> = HashMap::new();
+
+		loop {
+			if let Some(&mu) = seen.get(&self.grid) {
+				break Cycle {
+					mu,
+					lambda: self.generation - mu,
+				};
+			}
+
+			seen.insert(self.grid.clone(), self.generation);
+			self.step();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{moore, moore_with_center, Automaton, Dense, Grid, Infinite, Sparse};
+	use crate::cycle::Cycle;
+
+	#[allow(clippy::trivially_copy_pass_by_ref)]
+	fn life(alive: &bool, neighbors: &[bool]) -> bool {
+		let live = neighbors.iter().filter(|&&n| n).count();
+		live == 3 || (*alive && live == 2)
+	}
+
+	fn parse(rows: &str) -> Dense<bool> {
+		Dense::from_rows(rows.lines().map(|row| row.chars().map(|c| c == '#'))).unwrap()
+	}
+
+	#[test]
+	fn from_rows_rejects_ragged_rows() {
+		assert_eq!(Dense::from_rows([vec![1, 2], vec![3]]), None);
+	}
+
+	#[test]
+	fn dense_blinker_cycles() {
+		let vertical = parse(".....\n..#..\n..#..\n..#..\n.....");
+		let horizontal = parse(".....\n.....\n.###.\n.....\n.....");
+
+		let mut automaton = Automaton::new(vertical.clone(), moore, life);
+		assert!(automaton.step());
+		assert_eq!(automaton.grid(), &horizontal);
+
+		let mut automaton = Automaton::new(vertical, moore, life);
+		assert_eq!(automaton.find_cycle(), Cycle { mu: 0, lambda: 2 });
+	}
+
+	#[test]
+	fn dense_edges_see_whole_neighborhoods() {
+		let grid = parse("#.\n.#");
+
+		// Picking out the center of the block only works if the block is complete.
+		let mut automaton = Automaton::new(grid.clone(), moore_with_center, |_, block| block[4]);
+		assert!(!automaton.step());
+		assert_eq!(automaton.grid(), &grid);
+		assert_eq!(grid.get((-1, 0)), Some(false));
+	}
+
+	#[test]
+	fn dense_block_is_stable() {
+		let block = parse("....\n.##.\n.##.\n....");
+
+		let mut automaton = Automaton::new(block.clone(), moore, life);
+		assert_eq!(automaton.step_until_stable(), 1);
+		assert_eq!(automaton.into_grid(), block);
+	}
+
+	#[test]
+	fn step_n_skips_once_stable() {
+		let mut automaton = Automaton::new(parse("#.\n.."), moore, life);
+
+		assert_eq!(
+			automaton.step_n(1_000_000).cells().filter(|&&c| c).count(),
+			0
+		);
+		assert_eq!(automaton.generation(), 1_000_000);
+	}
+
+	#[test]
+	fn sparse_glider_translates() {
+		let glider: Sparse<(i32, i32)> = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+			.into_iter()
+			.collect();
+
+		let mut automaton = Automaton::new(glider.clone(), moore, life);
+		automaton.step_n(4);
+
+		let moved: Sparse<(i32, i32)> = glider.iter().map(|&(x, y)| (x + 1, y + 1)).collect();
+		assert_eq!(automaton.grid(), &moved);
+	}
+
+	#[test]
+	fn sparse_blinker_cycles() {
+		let blinker: Sparse<(i32, i32)> = [(1, 0), (1, 1), (1, 2)].into_iter().collect();
+
+		let mut automaton = Automaton::new(blinker, moore, life);
+		assert_eq!(automaton.find_cycle(), Cycle { mu: 0, lambda: 2 });
+	}
+
+	#[test]
+	fn infinite_block_is_stable() {
+		let grid = Infinite::new(parse("##\n##"), false);
+
+		let mut automaton = Automaton::new(grid.clone(), moore, life);
+		assert_eq!(automaton.step_until_stable(), 1);
+		assert_eq!(automaton.into_grid(), grid);
+	}
+
+	#[test]
+	fn infinite_blinker_cycles() {
+		let grid = Infinite::new(parse("#\n#\n#"), false);
+
+		let mut automaton = Automaton::new(grid, moore, life);
+		assert_eq!(automaton.find_cycle(), Cycle { mu: 0, lambda: 2 });
+		assert_eq!(automaton.grid().origin(), (0, 0));
+	}
+
+	#[test]
+	fn infinite_background_flips() {
+		// A rule under which an all-dark neighborhood lights up, and an all-lit one goes dark.
+		let rule = |_: &bool, block: &[bool]| !block[4];
+
+		let grid = Infinite::new(parse("#"), false);
+		let mut automaton = Automaton::new(grid, moore_with_center, rule);
+
+		automaton.step();
+		assert!(*automaton.grid().background());
+		// Only the one cell which differs from the background is kept in the window.
+		assert_eq!(automaton.grid().origin(), (0, 0));
+		assert_eq!(automaton.grid().window().width(), 1);
+		assert_eq!(automaton.grid().get((0, 0)), Some(false));
+		assert_eq!(automaton.grid().get((1, 1)), Some(true));
+		assert_eq!(automaton.grid().get((100, -100)), Some(true));
+
+		automaton.step();
+		assert!(!*automaton.grid().background());
+		assert_eq!(automaton.grid().get((0, 0)), Some(true));
+	}
+}
@@ -0,0 +1,260 @@
+//! Cycle detection for repeatedly-applied functions.
+//!
+//! Puzzles asking for the state after some enormous number of steps usually fall into a loop
+//! long before then.  Given a starting state and a step function, these helpers find where that
+//! loop starts (`mu`) and how long it is (`lambda`), from which the state at any step can be
+//! recovered without simulating every step.
+//!
+//! The `_by_key` variants compare a projection of each state instead of the state itself.  The
+//! projection must capture everything that influences future steps, or a "repeat" may be found
+//! where there is none.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// The shape of the sequence `x_0, f(x_0), f(f(x_0)), ...`: the first `mu` states are never
+/// seen again, and from there on the sequence repeats every `lambda` steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+	pub mu: usize,
+	pub lambda: usize,
+}
+
+impl Cycle {
+	/// Maps step `n` onto the earliest step with the same state.
+	pub fn reduce(&self, n: usize) -> usize {
+		if n < self.mu {
+			n
+		} else {
+			self.mu + (n - self.mu) % self.lambda
+		}
+	}
+}
+
+/// Finds the cycle with Floyd's tortoise-and-hare algorithm, in constant memory.
+pub fn floyd<T: Clone + PartialEq>(start: T, f: impl FnMut(&T) -> T) -> Cycle {
+	floyd_by(start, f, PartialEq::eq)
+}
+
+pub fn floyd_by_key<T: Clone, K: PartialEq>(
+	start: T,
+	f: impl FnMut(&T) -> T,
+	mut key: impl FnMut(&T) -> K,
+) -> Cycle {
+	floyd_by(start, f, |a, b| key(a) == key(b))
+}
+
+fn floyd_by<T: Clone>(
+	start: T,
+	mut f: impl FnMut(&T) -> T,
+	mut eq: impl FnMut(&T, &T) -> bool,
+) -> Cycle {
+	// Find some state inside of the cycle, where the hare has lapped the tortoise.
+	let mut tortoise = f(&start);
+	let mut hare = f(&tortoise);
+
+	while !eq(&tortoise, &hare) {
+		tortoise = f(&tortoise);
+		let halfway = f(&hare);
+		hare = f(&halfway);
+	}
+
+	// The hare is now a multiple of the cycle length ahead, so walking both forward in lockstep
+	// from the start finds the first state of the cycle.
+	let mut mu = 0;
+	let mut tortoise = start;
+
+	while !eq(&tortoise, &hare) {
+		tortoise = f(&tortoise);
+		hare = f(&hare);
+		mu += 1;
+	}
+
+	let mut lambda = 1;
+	let mut hare = f(&tortoise);
+
+	while !eq(&tortoise, &hare) {
+		hare = f(&hare);
+		lambda += 1;
+	}
+
+	Cycle { mu, lambda }
+}
+
+/// Finds the cycle with Brent's algorithm, in constant memory and usually fewer steps than
+/// [`floyd`].
+pub fn brent<T: Clone + PartialEq>(start: T, f: impl FnMut(&T) -> T) -> Cycle {
+	brent_by(start, f, PartialEq::eq)
+}
+
+pub fn brent_by_key<T: Clone, K: PartialEq>(
+	start: T,
+	f: impl FnMut(&T) -> T,
+	mut key: impl FnMut(&T) -> K,
+) -> Cycle {
+	brent_by(start, f, |a, b| key(a) == key(b))
+}
+
+fn brent_by<T: Clone>(
+	start: T,
+	mut f: impl FnMut(&T) -> T,
+	mut eq: impl FnMut(&T, &T) -> bool,
+) -> Cycle {
+	// Leave the tortoise at successive powers of two until the hare runs into it.
+	let mut power = 1;
+	let mut lambda = 1;
+	let mut tortoise = start.clone();
+	let mut hare = f(&start);
+
+	while !eq(&tortoise, &hare) {
+		if power == lambda {
+			tortoise = hare.clone();
+			power *= 2;
+			lambda = 0;
+		}
+
+		hare = f(&hare);
+		lambda += 1;
+	}
+
+	// Start the hare a full cycle ahead, then walk both forward until they meet.
+	let mut hare = start.clone();
+	for _ in 0..lambda {
+		hare = f(&hare);
+	}
+
+	let mut mu = 0;
+	let mut tortoise = start;
+
+	while !eq(&tortoise, &hare) {
+		tortoise = f(&tortoise);
+		hare = f(&hare);
+		mu += 1;
+	}
+
+	Cycle { mu, lambda }
+}
+
+/// Recomputes the state at step `n` by replaying only as many steps as needed.
+pub fn state_at<T>(start: T, mut f: impl FnMut(&T) -> T, cycle: Cycle, n: usize) -> T {
+	(0..cycle.reduce(n)).fold(start, |state, _| f(&state))
+}
+
+/// Every state visited up to (but not including) the first repeat.
+#[derive(Clone, Debug)]
+pub struct History<T> {
+	states: Vec<T>,
+	cycle: Cycle,
+}
+
+impl<T> History<T> {
+	pub fn cycle(&self) -> Cycle {
+		self.cycle
+	}
+
+	/// The state at step `n`, looked up rather than recomputed.
+	pub fn state_at(&self, n: usize) -> &T {
+		&self.states[self.cycle.reduce(n)]
+	}
+
+	/// All states in the order they were visited: the lead-in followed by one pass of the cycle.
+	pub fn states(&self) -> &[T] {
+		&self.states
+	}
+}
+
+/// Finds the cycle by remembering every state seen, which takes only `mu + lambda` steps but
+/// keeps all of those states around.
+pub fn history<T: Clone + Eq + Hash>(start: T, f: impl FnMut(&T) -> T) -> History<T> {
+	history_by_key(start, f, Clone::clone)
+}
+
+pub fn history_by_key<T, K: Eq + Hash>(
+	start: T,
+	mut f: impl FnMut(&T) -> T,
+	mut key: impl FnMut(&T) -> K,
+) -> History<T> {
+	let mut seen: HashMap<K, usize> = HashMap::new();
+	let mut states = vec![start];
+
+	loop {
+		let current = states.last().unwrap();
+		let idx = states.len() - 1;
+
+		if let Some(&mu) = seen.get(&key(current)) {
+			states.pop();
+
+			break History {
+				states,
+				cycle: Cycle {
+					mu,
+					lambda: idx - mu,
+				},
+			};
+		}
+
+		seen.insert(key(current), idx);
+
+		let next = f(current);
+		states.push(next);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{brent, brent_by_key, floyd, floyd_by_key, history, history_by_key, state_at, Cycle};
+
+	// 0, 1, 2, 3, 4, 5, 6, 3, 4, 5, 6, ...
+	#[allow(clippy::trivially_copy_pass_by_ref)]
+	fn lollipop(n: &u32) -> u32 {
+		if *n == 6 {
+			3
+		} else {
+			n + 1
+		}
+	}
+
+	const LOLLIPOP: Cycle = Cycle { mu: 3, lambda: 4 };
+
+	#[test]
+	fn reduce() {
+		assert_eq!(LOLLIPOP.reduce(2), 2);
+		assert_eq!(LOLLIPOP.reduce(7), 3);
+		assert_eq!(LOLLIPOP.reduce(1_000_000_000), 4);
+	}
+
+	#[test]
+	fn algorithms_agree() {
+		assert_eq!(floyd(0, lollipop), LOLLIPOP);
+		assert_eq!(brent(0, lollipop), LOLLIPOP);
+		assert_eq!(history(0, lollipop).cycle(), LOLLIPOP);
+	}
+
+	#[test]
+	fn fixed_point_from_the_start() {
+		let cycle = Cycle { mu: 0, lambda: 1 };
+
+		assert_eq!(floyd(7, |n| *n), cycle);
+		assert_eq!(brent(7, |n| *n), cycle);
+		assert_eq!(history(7, |n| *n).cycle(), cycle);
+	}
+
+	#[test]
+	fn by_key() {
+		// The counter keeps growing, but only its residue determines what happens next.
+		let f = |&(residue, count): &(u32, u32)| ((residue + 1) % 5, count + 1);
+		let cycle = Cycle { mu: 0, lambda: 5 };
+
+		assert_eq!(floyd_by_key((0, 0), f, |s| s.0), cycle);
+		assert_eq!(brent_by_key((0, 0), f, |s| s.0), cycle);
+		assert_eq!(history_by_key((0, 0), f, |s| s.0).cycle(), cycle);
+	}
+
+	#[test]
+	fn states_at_huge_steps() {
+		let history = history(0, lollipop);
+
+		assert_eq!(history.states(), &[0, 1, 2, 3, 4, 5, 6]);
+		assert_eq!(*history.state_at(1_000_000_000), 4);
+		assert_eq!(state_at(0, lollipop, LOLLIPOP, 1_000_000_000), 4);
+	}
+}
@@ -0,0 +1,299 @@
+//! Disjoint-set forests (union-find), for grouping things into connected components.
+//!
+//! [`DisjointSet`] works on the dense indices `0..n`; [`KeyedDisjointSet`] wraps it to work on any
+//! hashable key, assigning indices as new keys show up.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// A union-find over the indices `0..len`, using path compression and union by size.
+#[derive(Clone, Debug)]
+pub struct DisjointSet {
+	parent: Vec<usize>,
+	size: Vec<usize>,
+	components: usize,
+}
+
+impl DisjointSet {
+	/// Creates `len` singleton components.
+	pub fn new(len: usize) -> Self {
+		Self {
+			parent: (0..len).collect(),
+			size: vec![1; len],
+			components: len,
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.parent.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.parent.is_empty()
+	}
+
+	/// Adds a new singleton component, returning its index.
+	pub fn push(&mut self) -> usize {
+		let idx = self.parent.len();
+
+		self.parent.push(idx);
+		self.size.push(1);
+		self.components += 1;
+
+		idx
+	}
+
+	/// Finds the representative of the component containing `x`.
+	pub fn find(&mut self, x: usize) -> usize {
+		let mut root = x;
+
+		while self.parent[root] != root {
+			root = self.parent[root];
+		}
+
+		// Point everything along the way straight at the root, so later lookups are quick.
+		let mut x = x;
+
+		while self.parent[x] != root {
+			let next = self.parent[x];
+			self.parent[x] = root;
+			x = next;
+		}
+
+		root
+	}
+
+	/// Merges the components containing `a` and `b`, returning `false` if they were already the
+	/// same component.
+	pub fn union(&mut self, a: usize, b: usize) -> bool {
+		let (a, b) = (self.find(a), self.find(b));
+
+		if a == b {
+			return false;
+		}
+
+		// Hang the smaller tree under the larger one to keep the trees shallow.
+		let (large, small) = if self.size[a] < self.size[b] {
+			(b, a)
+		} else {
+			(a, b)
+		};
+
+		self.parent[small] = large;
+		self.size[large] += self.size[small];
+		self.components -= 1;
+
+		true
+	}
+
+	pub fn same(&mut self, a: usize, b: usize) -> bool {
+		self.find(a) == self.find(b)
+	}
+
+	/// The number of members in the component containing `x`.
+	pub fn size_of(&mut self, x: usize) -> usize {
+		let root = self.find(x);
+		self.size[root]
+	}
+
+	/// The number of distinct components.
+	pub fn component_count(&self) -> usize {
+		self.components
+	}
+
+	/// The size of each component, in no particular order.
+	pub fn component_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+		self
+			.parent
+			.iter()
+			.enumerate()
+			.filter(|&(idx, &parent)| idx == parent)
+			.map(|(root, _)| self.size[root])
+	}
+
+	/// Collects the members of every component.  Members are listed in ascending order, and
+	/// components are ordered by their smallest member.
+	pub fn components(&mut self) -> Vec<Vec<usize>> {
+		let mut by_root: HashMap<usize, usize> = HashMap::new();
+		let mut components: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+
+		for x in 0..self.len() {
+			let root = self.find(x);
+
+			let idx = *by_root.entry(root).or_insert_with(|| {
+				components.push(Vec::new());
+				components.len() - 1
+			});
+
+			components[idx].push(x);
+		}
+
+		components
+	}
+}
+
+/// A union-find over arbitrary keys.  Keys not seen before are added as singleton components.
+#[derive(Clone, Debug)]
+pub struct KeyedDisjointSet<K> {
+	indices: HashMap<K, usize>,
+	keys: Vec<K>,
+	inner: DisjointSet,
+}
+
+impl<K> Default for KeyedDisjointSet<K> {
+	fn default() -> Self {
+		Self {
+			indices: HashMap::new(),
+			keys: Vec::new(),
+			inner: DisjointSet::new(0),
+		}
+	}
+}
+
+impl<K: Clone + Eq + Hash> KeyedDisjointSet<K> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.keys.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.keys.is_empty()
+	}
+
+	pub fn contains(&self, key: &K) -> bool {
+		self.indices.contains_key(key)
+	}
+
+	/// Adds `key` as a singleton component if it hasn't been seen yet, returning its index.
+	pub fn insert(&mut self, key: K) -> usize {
+		if let Some(&idx) = self.indices.get(&key) {
+			return idx;
+		}
+
+		let idx = self.inner.push();
+		self.indices.insert(key.clone(), idx);
+		self.keys.push(key);
+
+		idx
+	}
+
+	/// Finds the representative key of the component containing `key`, if `key` is known.
+	pub fn find(&mut self, key: &K) -> Option<&K> {
+		let idx = *self.indices.get(key)?;
+		let root = self.inner.find(idx);
+
+		Some(&self.keys[root])
+	}
+
+	/// Merges the components containing `a` and `b` (inserting either if needed), returning
+	/// `false` if they were already the same component.
+	pub fn union(&mut self, a: K, b: K) -> bool {
+		let (a, b) = (self.insert(a), self.insert(b));
+		self.inner.union(a, b)
+	}
+
+	/// Whether `a` and `b` are known and in the same component.
+	pub fn same(&mut self, a: &K, b: &K) -> bool {
+		match (self.indices.get(a), self.indices.get(b)) {
+			(Some(&a), Some(&b)) => self.inner.same(a, b),
+			_ => false,
+		}
+	}
+
+	/// The number of members in the component containing `key`, if `key` is known.
+	pub fn size_of(&mut self, key: &K) -> Option<usize> {
+		let idx = *self.indices.get(key)?;
+		Some(self.inner.size_of(idx))
+	}
+
+	pub fn component_count(&self) -> usize {
+		self.inner.component_count()
+	}
+
+	pub fn component_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+		self.inner.component_sizes()
+	}
+
+	/// Collects the members of every component, each in the order its keys were first inserted.
+	pub fn components(&mut self) -> Vec<Vec<&K>> {
+		self
+			.inner
+			.components()
+			.into_iter()
+			.map(|component| component.into_iter().map(|idx| &self.keys[idx]).collect())
+			.collect()
+	}
+}
+
+impl<K: Clone + Eq + Hash> FromIterator<(K, K)> for KeyedDisjointSet<K> {
+	fn from_iter<I: IntoIterator<Item = (K, K)>>(iter: I) -> Self {
+		let mut set = Self::new();
+
+		for (a, b) in iter {
+			set.union(a, b);
+		}
+
+		set
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{DisjointSet, KeyedDisjointSet};
+
+	#[test]
+	fn dense_unions() {
+		let mut set = DisjointSet::new(6);
+		assert_eq!(set.component_count(), 6);
+
+		assert!(set.union(0, 1));
+		assert!(set.union(1, 2));
+		assert!(!set.union(2, 0));
+		assert!(set.union(4, 5));
+
+		assert!(set.same(0, 2));
+		assert!(!set.same(0, 3));
+		assert_eq!(set.size_of(1), 3);
+		assert_eq!(set.size_of(3), 1);
+		assert_eq!(set.component_count(), 3);
+		assert_eq!(set.components(), vec![vec![0, 1, 2], vec![3], vec![4, 5]]);
+
+		let mut sizes: Vec<usize> = set.component_sizes().collect();
+		sizes.sort_unstable();
+		assert_eq!(sizes, vec![1, 2, 3]);
+	}
+
+	#[test]
+	fn long_chain_compresses() {
+		let mut set = DisjointSet::new(100_000);
+
+		for x in 1..set.len() {
+			set.union(x - 1, x);
+		}
+
+		assert_eq!(set.component_count(), 1);
+		assert_eq!(set.size_of(99_999), 100_000);
+	}
+
+	#[test]
+	fn keyed_pipes() {
+		// The 2017 day 12 example, as pairs of connected programs.
+		let mut set: KeyedDisjointSet<u32> = [(0, 2), (2, 3), (2, 4), (3, 4), (4, 6), (5, 6), (1, 1)]
+			.into_iter()
+			.collect();
+
+		assert_eq!(set.len(), 7);
+		assert_eq!(set.size_of(&0), Some(6));
+		assert_eq!(set.size_of(&7), None);
+		assert_eq!(set.component_count(), 2);
+		assert!(set.same(&0, &5));
+		assert!(!set.same(&0, &1));
+		assert!(!set.same(&0, &7));
+		assert_eq!(
+			set.components(),
+			vec![vec![&0, &2, &3, &4, &6, &5], vec![&1]]
+		);
+	}
+}
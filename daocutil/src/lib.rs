@@ -125,21 +125,50 @@ macro_rules! generate_solvers {
 
 #[macro_export]
 macro_rules! generate_main {
-	($loc:path) => {
-		daocutil::generate_main!(daocutil::generate_solvers!($loc));
+	($loc:path $(; $($command:literal => $handler:path),+ $(,)?)?) => {
+		daocutil::generate_main!(daocutil::generate_solvers!($loc) $(; $($command => $handler),+)?);
 	};
 
-	($solvers_expr:expr) => {
+	($solvers_expr:expr $(; $($command:literal => $handler:path),+ $(,)?)?) => {
 		fn main() -> Result<(), Box<dyn std::error::Error>> {
 			let solvers: std::collections::HashMap<u8, daocutil::Solver> = { $solvers_expr };
 
-			let mut args = std::env::args();
+			// Subcommands run in place of the solver, on the same input.  Those named like flags
+			// (starting with `--`) can go anywhere on the command line; the rest must come first.
+			let commands: std::collections::HashMap<&str, daocutil::Solver> =
+				std::collections::HashMap::from([$($(($command, $handler as daocutil::Solver)),+)?]);
+
+			// Pull out any flags, leaving just the positional arguments.
+			let mut positional = Vec::new();
+			let mut command = None;
+
+			for arg in std::env::args() {
+				match daocutil::viz::parse_flag(&arg) {
+					Some(fps) => daocutil::viz::enable(fps?),
+					None if arg.starts_with("--") && commands.contains_key(arg.as_str()) => {
+						command = commands.get(arg.as_str()).copied();
+					}
+					None => positional.push(arg),
+				}
+			}
+
+			let mut args = positional.into_iter();
 
 			let _ = args.next();
 
-			if let Some(ident) = args.next() {
+			let mut ident = args.next();
+
+			if command.is_none() {
+				command = ident.as_deref().and_then(|name| commands.get(name)).copied();
+
+				if command.is_some() {
+					ident = args.next();
+				}
+			}
+
+			if let Some(ident) = ident {
 				if let Some(ident) = daocutil::parse_day_identifier(&ident) {
-					if let Some(handler) = solvers.get(&ident) {
+					if let Some(handler) = command.or_else(|| solvers.get(&ident).copied()) {
 						let data: String = match (
 							std::fs::File::open(format!("inputs/day{:02}", ident)),
 							args.next(),
@@ -195,6 +224,15 @@ macro_rules! test_examples {
 	};
 }
 
+pub mod automaton;
+pub mod cycle;
+pub mod dsu;
+pub mod ocr;
+pub mod parse;
+pub mod particles;
+pub mod tree;
+pub mod viz;
+
 mod neighbors;
 pub use neighbors::*;
 
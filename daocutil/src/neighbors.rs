@@ -1,4 +1,4 @@
-const OFFSETS_INCLUDING_DIAGS: [(i32, i32); 8] = [
+pub(crate) const OFFSETS_INCLUDING_DIAGS: [(i32, i32); 8] = [
 	(-1, 1),
 	(0, 1),
 	(1, 1),
@@ -9,7 +9,7 @@ const OFFSETS_INCLUDING_DIAGS: [(i32, i32); 8] = [
 	(1, -1),
 ];
 
-const OFFSETS_EXCLUDING_DIAGS: [(i32, i32); 4] = [(0, 1), (-1, 0), (1, 0), (0, -1)];
+pub(crate) const OFFSETS_EXCLUDING_DIAGS: [(i32, i32); 4] = [(0, 1), (-1, 0), (1, 0), (0, -1)];
 
 pub fn get(pos: &(i32, i32)) -> impl Iterator<Item = (i32, i32)> + '_ {
 	OFFSETS_INCLUDING_DIAGS
@@ -0,0 +1,126 @@
+//! Reading the block capitals that some puzzles draw their answers in.
+//!
+//! Letters are six pixels tall and (mostly) four wide, with at least one blank column between
+//! neighbours.  Pictures are split into letters at blank columns, so letters don't need to sit at
+//! fixed positions.  Only the letters known to appear in puzzles are recognised.
+
+/// Each known letter, drawn with `#` for lit pixels and trimmed to its lit columns.
+const LETTERS: [(char, [&str; 6]); 18] = [
+	('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+	('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+	('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+	('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+	('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+	('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+	('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+	('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+	('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+	('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+	('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+	('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+	('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+	('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+	('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+	('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+	('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+	('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Reads the letters drawn by `rows` of pixels, where `true` is lit.  Blank rows above and below
+/// the letters are ignored, and rows may be of different lengths.
+///
+/// Returns `None` if the letters aren't six pixels tall, or any of them isn't recognised.
+pub fn decode(rows: &[Vec<bool>]) -> Option<String> {
+	let first = rows.iter().position(|row| row.contains(&true))?;
+	let last = rows.iter().rposition(|row| row.contains(&true))?;
+	let rows = &rows[first..=last];
+
+	if rows.len() != 6 {
+		return None;
+	}
+
+	let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+	let lit = |x: usize, y: usize| rows[y].get(x).copied().unwrap_or_default();
+	let blank = |x: usize| (0..rows.len()).all(|y| !lit(x, y));
+
+	let mut text = String::new();
+	let mut x = 0;
+
+	while x < width {
+		if blank(x) {
+			x += 1;
+			continue;
+		}
+
+		let start = x;
+
+		while x < width && !blank(x) {
+			x += 1;
+		}
+
+		let glyph: Vec<String> = (0..rows.len())
+			.map(|y| {
+				(start..x)
+					.map(|x| if lit(x, y) { '#' } else { '.' })
+					.collect()
+			})
+			.collect();
+
+		let &(letter, _) = LETTERS
+			.iter()
+			.find(|(_, pattern)| glyph.iter().zip(pattern).all(|(a, b)| a == b))?;
+
+		text.push(letter);
+	}
+
+	Some(text)
+}
+
+/// Reads letters from a picture drawn with `#` for lit pixels, one line per row.
+pub fn decode_str(picture: &str) -> Option<String> {
+	let rows: Vec<Vec<bool>> = picture
+		.lines()
+		.map(|line| line.chars().map(|c| c == '#').collect())
+		.collect();
+
+	decode(&rows)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{decode, decode_str};
+
+	#[test]
+	fn reads_letters() {
+		let picture = "
+.##..###..####.#..#...###.#...#
+#..#.#..#.#....#..#....#..#...#
+#..#.###..###..####....#...#.#.
+####.#..#.#....#..#....#....#..
+#..#.#..#.#....#..#....#....#..
+#..#.###..####.#..#...###...#..
+";
+
+		assert_eq!(decode_str(picture), Some("ABEHIY".to_string()));
+	}
+
+	#[test]
+	fn ignores_spacing() {
+		let picture = "
+  ####    #...
+  ...#    #...
+  ..#.    #...
+  .#..    #...
+  #...    #...
+  ####    ####";
+
+		assert_eq!(decode_str(picture), Some("ZL".to_string()));
+	}
+
+	#[test]
+	fn rejects_unknown_shapes() {
+		assert_eq!(decode_str("#\n#\n#\n#\n#\n#"), None);
+		assert_eq!(decode_str(".##.\n#..#\n.##."), None);
+		assert_eq!(decode(&[]), None);
+	}
+}
@@ -0,0 +1,373 @@
+//! Helpers for turning puzzle input into values without panicking on malformed input.
+//!
+//! Failures are reported as a [`ParseError`] that knows where in the input it happened: the
+//! column is filled in by whatever found the problem, and the line by [`lines`] (or
+//! [`ParseError::on_line`]) as the error passes through.
+//!
+//! Most line formats can be handled with [`scan!`](crate::scan), which matches a line against a
+//! pattern with `{}` placeholders and parses each placeholder as a given type:
+//!
+//! ```
+//! let (count, from, to) = daocutil::scan!("move 3 from 1 to 2", "move {} from {} to {}", usize, u8, u8)?;
+//! assert_eq!((count, from, to), (3, 1, 2));
+//! # Ok::<(), daocutil::parse::ParseError>(())
+//! ```
+
+use core::{fmt, str::FromStr};
+
+/// What went wrong while parsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+	/// A literal part of a pattern was missing.
+	Expected(String),
+	/// A token was found but could not be converted into the wanted value.
+	Invalid { token: String, reason: String },
+	/// Input remained after everything expected had been matched.
+	Trailing(String),
+}
+
+/// A parse failure, along with where it happened.  Lines and columns are 1-based; columns count
+/// bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+	pub line: Option<usize>,
+	pub column: usize,
+	pub kind: ErrorKind,
+}
+
+impl ParseError {
+	pub fn new(column: usize, kind: ErrorKind) -> Self {
+		Self {
+			line: None,
+			column,
+			kind,
+		}
+	}
+
+	/// An error for a `token` at `column` which could not be converted.
+	pub fn invalid(column: usize, token: &str, reason: impl fmt::Display) -> Self {
+		Self::new(
+			column,
+			ErrorKind::Invalid {
+				token: token.to_string(),
+				reason: reason.to_string(),
+			},
+		)
+	}
+
+	/// Records the line the error happened on, unless one was already recorded.
+	#[must_use]
+	pub fn on_line(self, line: usize) -> Self {
+		Self {
+			line: self.line.or(Some(line)),
+			..self
+		}
+	}
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(line) = self.line {
+			write!(f, "line {line}, ")?;
+		}
+
+		write!(f, "column {}: ", self.column)?;
+
+		match &self.kind {
+			ErrorKind::Expected(literal) => write!(f, "expected `{literal}`"),
+			ErrorKind::Invalid { token, reason } => write!(f, "invalid `{token}`: {reason}"),
+			ErrorKind::Trailing(rest) => write!(f, "unexpected trailing `{rest}`"),
+		}
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses every line of `input` with `f`, attaching line numbers to any error.
+pub fn lines<T>(
+	input: &str,
+	mut f: impl FnMut(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+	input
+		.lines()
+		.enumerate()
+		.map(|(idx, line)| f(line).map_err(|error| error.on_line(idx + 1)))
+		.collect()
+}
+
+/// Splits `input` into groups of lines separated by one or more blank lines.  Each block is a
+/// slice of `input` running from the start of its first line to the end of its last.
+pub fn blocks(input: &str) -> impl Iterator<Item = &str> {
+	let mut rest = input;
+
+	core::iter::from_fn(move || {
+		// Skip past any blank lines before the block.
+		while !rest.is_empty() {
+			let (line, after) = rest.split_once('\n').unwrap_or((rest, ""));
+
+			if !line.trim().is_empty() {
+				break;
+			}
+
+			rest = after;
+		}
+
+		if rest.is_empty() {
+			return None;
+		}
+
+		let block = rest;
+		let mut len = 0;
+
+		while len < block.len() {
+			let (line, after) = block[len..].split_once('\n').unwrap_or((&block[len..], ""));
+
+			if line.trim().is_empty() {
+				break;
+			}
+
+			len = block.len() - after.len();
+		}
+
+		rest = &block[len..];
+
+		Some(block[..len].trim_end())
+	})
+}
+
+/// Finds every integer in `s`, along with the 1-based column it starts at.  A `-` directly in
+/// front of the digits is treated as a sign, unless it directly follows another number.
+pub fn integer_tokens(s: &str) -> impl Iterator<Item = (usize, &str)> {
+	let bytes = s.as_bytes();
+	let mut idx = 0;
+
+	core::iter::from_fn(move || {
+		while idx < bytes.len() && !bytes[idx].is_ascii_digit() {
+			idx += 1;
+		}
+
+		if idx == bytes.len() {
+			return None;
+		}
+
+		let mut start = idx;
+
+		// Only count the `-` as a sign if it isn't joining two numbers, as in a range like `1-3`.
+		if start > 0 && bytes[start - 1] == b'-' && (start < 2 || !bytes[start - 2].is_ascii_digit()) {
+			start -= 1;
+		}
+
+		while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+			idx += 1;
+		}
+
+		Some((start + 1, &s[start..idx]))
+	})
+}
+
+/// Parses every integer in `s` (see [`integer_tokens`]), ignoring whatever separates them.
+pub fn integers<T>(s: &str) -> Result<Vec<T>, ParseError>
+where
+	T: FromStr,
+	T::Err: fmt::Display,
+{
+	integer_tokens(s).map(field).collect()
+}
+
+/// Parses a single token found at `column`.
+pub fn field<T>((column, token): (usize, &str)) -> Result<T, ParseError>
+where
+	T: FromStr,
+	T::Err: fmt::Display,
+{
+	token
+		.parse()
+		.map_err(|error| ParseError::invalid(column, token, error))
+}
+
+/// Matches `input` against `pattern`, returning the text (and 1-based column) captured by each
+/// `{}` placeholder.
+///
+/// A placeholder captures everything up to the first occurrence of the literal following it, or
+/// up to the end of the input if the pattern ends in a placeholder.  A literal at the very end of
+/// the pattern must end the input.
+pub fn scan_fields<'i>(input: &'i str, pattern: &str) -> Result<Vec<(usize, &'i str)>, ParseError> {
+	let mut literals = pattern.split("{}");
+	let mut fields = Vec::new();
+
+	let first = literals.next().unwrap_or_default();
+	let mut pos = input
+		.starts_with(first)
+		.then_some(first.len())
+		.ok_or_else(|| ParseError::new(1, ErrorKind::Expected(first.to_string())))?;
+
+	let mut literals = literals.peekable();
+
+	while let Some(literal) = literals.next() {
+		let rest = &input[pos..];
+
+		let end = if literal.is_empty() && literals.peek().is_none() {
+			// The pattern ends with this placeholder, so it takes everything left.
+			Some(rest.len())
+		} else if literals.peek().is_none() {
+			rest.strip_suffix(literal).map(str::len)
+		} else {
+			rest.find(literal)
+		}
+		.ok_or_else(|| ParseError::new(pos + 1, ErrorKind::Expected(literal.to_string())))?;
+
+		fields.push((pos + 1, &rest[..end]));
+		pos += end + literal.len();
+	}
+
+	if pos < input.len() {
+		return Err(ParseError::new(
+			pos + 1,
+			ErrorKind::Trailing(input[pos..].to_string()),
+		));
+	}
+
+	Ok(fields)
+}
+
+/// The number of `{}` placeholders in a [`scan!`](crate::scan) pattern, counted at compile time.
+#[doc(hidden)]
+pub const fn placeholders(pattern: &str) -> usize {
+	let bytes = pattern.as_bytes();
+	let mut count = 0;
+	let mut idx = 0;
+
+	while idx + 1 < bytes.len() {
+		if bytes[idx] == b'{' && bytes[idx + 1] == b'}' {
+			count += 1;
+			idx += 2;
+		} else {
+			idx += 1;
+		}
+	}
+
+	count
+}
+
+/// Matches a string against a pattern containing `{}` placeholders, parsing each placeholder as
+/// the corresponding type and returning them as a tuple.
+///
+/// Evaluates to a `Result<(T, ...), ParseError>`; see [`parse::scan_fields`](crate::parse::scan_fields)
+/// for how placeholders are matched.
+///
+/// The pattern must be a string literal with exactly one placeholder per type, which is checked
+/// at compile time:
+///
+/// ```compile_fail,E0080
+/// daocutil::scan!("1 2", "{} {}", u8);
+/// ```
+///
+/// ```compile_fail,E0080
+/// daocutil::scan!("1", "{}", u8, u8);
+/// ```
+#[macro_export]
+macro_rules! scan {
+	($input:expr, $pattern:literal, $($ty:ty),+ $(,)?) => {{
+		const _: () = assert!(
+			$crate::parse::placeholders($pattern) == [$(stringify!($ty)),+].len(),
+			"scan! needs exactly one type per placeholder in its pattern",
+		);
+
+		$crate::parse::scan_fields($input, $pattern).and_then(|fields| {
+			let mut fields = fields.into_iter();
+
+			Ok(($(
+				$crate::parse::field::<$ty>(
+					fields.next().expect("scan_fields gives one field per placeholder"),
+				)?,
+			)+))
+		})
+	}};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{blocks, integers, lines, scan_fields, ErrorKind, ParseError};
+
+	#[test]
+	fn integers_with_signs() {
+		assert_eq!(
+			integers::<i32>("p=<3,-7,0>, v=<-12,4,5>"),
+			Ok(vec![3, -7, 0, -12, 4, 5])
+		);
+		assert_eq!(integers::<u8>("2-4,6-8"), Ok(vec![2, 4, 6, 8]));
+	}
+
+	#[test]
+	fn integers_report_column() {
+		let error = integers::<u8>("ok 12 300").unwrap_err();
+
+		assert_eq!(error.column, 7);
+		assert!(matches!(error.kind, ErrorKind::Invalid { ref token, .. } if token == "300"));
+	}
+
+	#[test]
+	fn blocks_split_on_blank_lines() {
+		let input = "a\nb\n\n\nc\n  \nd\ne\n";
+
+		assert_eq!(blocks(input).collect::<Vec<_>>(), vec!["a\nb", "c", "d\ne"]);
+		assert_eq!(blocks("\n\n").count(), 0);
+		assert_eq!(blocks("a\r\n\r\nb\r\n").collect::<Vec<_>>(), vec!["a", "b"]);
+	}
+
+	#[test]
+	fn scan_tuple() {
+		assert_eq!(
+			crate::scan!("move 3 from 1 to 2", "move {} from {} to {}", usize, u8, u8),
+			Ok((3, 1, 2))
+		);
+		assert_eq!(
+			crate::scan!(
+				"light red bags contain 1 bright white bag.",
+				"{} bags contain {}.",
+				String,
+				String
+			),
+			Ok(("light red".to_string(), "1 bright white bag".to_string()))
+		);
+	}
+
+	#[test]
+	fn placeholder_counts() {
+		assert_eq!(super::placeholders("move {} from {} to {}"), 3);
+		assert_eq!(super::placeholders("{}){}"), 2);
+		assert_eq!(super::placeholders("{ }"), 0);
+		assert_eq!(super::placeholders(""), 0);
+	}
+
+	#[test]
+	fn scan_errors() {
+		assert_eq!(
+			scan_fields("mov 3", "move {}"),
+			Err(ParseError::new(1, ErrorKind::Expected("move ".into())))
+		);
+		assert_eq!(
+			scan_fields("3 apples", "{} pears"),
+			Err(ParseError::new(1, ErrorKind::Expected(" pears".into())))
+		);
+		assert_eq!(
+			scan_fields("x=1, y=2!", "x={}, y={}!?"),
+			Err(ParseError::new(8, ErrorKind::Expected("!?".into())))
+		);
+
+		let error = crate::scan!("x=1, y=two", "x={}, y={}", i32, i32).unwrap_err();
+		assert_eq!(error.column, 8);
+	}
+
+	#[test]
+	fn lines_attach_line_numbers() {
+		let error = lines("1\n2\nthree", |line| crate::scan!(line, "{}", u32)).unwrap_err();
+
+		assert_eq!(error.line, Some(3));
+		assert_eq!(error.column, 1);
+		assert_eq!(
+			error.to_string(),
+			"line 3, column 1: invalid `three`: invalid digit found in string"
+		);
+	}
+}
@@ -0,0 +1,112 @@
+//! Point particles moving through integer space in discrete ticks.
+//!
+//! Every tick, each particle's velocity is first changed by its acceleration, and then its
+//! position by its (new) velocity.  Where the acceleration comes from is up to the puzzle: it
+//! might be fixed per particle, or depend on where all the other particles are.
+//!
+//! When the acceleration along each axis only depends on positions along that same axis, the axes
+//! evolve independently.  [`Particle::axis`] splits a system up so that each axis can be studied,
+//! for example for cycles, on its own.
+
+/// A particle in `D` dimensions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Particle<const D: usize> {
+	pub position: [i64; D],
+	pub velocity: [i64; D],
+}
+
+impl<const D: usize> Particle<D> {
+	pub fn at_rest(position: [i64; D]) -> Self {
+		Self {
+			position,
+			velocity: [0; D],
+		}
+	}
+
+	/// The sum of the absolute values of the position's coordinates.
+	pub fn potential_energy(&self) -> i64 {
+		self.position.iter().map(|p| p.abs()).sum()
+	}
+
+	/// The sum of the absolute values of the velocity's coordinates.
+	pub fn kinetic_energy(&self) -> i64 {
+		self.velocity.iter().map(|v| v.abs()).sum()
+	}
+
+	/// The particle's motion along a single axis.
+	pub fn axis(&self, axis: usize) -> Particle<1> {
+		Particle {
+			position: [self.position[axis]],
+			velocity: [self.velocity[axis]],
+		}
+	}
+}
+
+/// Advances every particle by one tick.  `acceleration` is given the index of a particle and the
+/// state of every particle at the start of the tick.
+pub fn step<const D: usize>(
+	particles: &mut [Particle<D>],
+	acceleration: impl Fn(usize, &[Particle<D>]) -> [i64; D],
+) {
+	let accelerations: Vec<[i64; D]> = (0..particles.len())
+		.map(|idx| acceleration(idx, particles))
+		.collect();
+
+	for (particle, acceleration) in particles.iter_mut().zip(accelerations) {
+		for ((position, velocity), acceleration) in particle
+			.position
+			.iter_mut()
+			.zip(&mut particle.velocity)
+			.zip(acceleration)
+		{
+			*velocity += acceleration;
+			*position += *velocity;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{step, Particle};
+
+	#[test]
+	fn constant_acceleration() {
+		let mut particles = [Particle {
+			position: [3, 0],
+			velocity: [2, 0],
+		}];
+
+		step(&mut particles, |_, _| [-1, 1]);
+		assert_eq!(particles[0].position, [4, 1]);
+		assert_eq!(particles[0].velocity, [1, 1]);
+
+		step(&mut particles, |_, _| [-1, 1]);
+		assert_eq!(particles[0].position, [4, 3]);
+		assert_eq!(particles[0].potential_energy(), 7);
+		assert_eq!(particles[0].kinetic_energy(), 2);
+	}
+
+	#[test]
+	fn accelerations_use_the_previous_tick() {
+		// Each particle is pushed towards the other, judging by where they were before either moved.
+		let mut particles = [Particle::at_rest([0]), Particle::at_rest([1])];
+
+		step(&mut particles, |idx, particles| {
+			let other = particles[1 - idx].position[0];
+			[(other - particles[idx].position[0]).signum()]
+		});
+
+		assert_eq!(particles.map(|particle| particle.position[0]), [1, 0]);
+		assert_eq!(
+			Particle {
+				position: [1, 2, 3],
+				velocity: [4, 5, 6],
+			}
+			.axis(1),
+			Particle {
+				position: [2],
+				velocity: [5],
+			}
+		);
+	}
+}
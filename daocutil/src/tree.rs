@@ -0,0 +1,223 @@
+//! Rooted trees built from parent-child pairs.
+//!
+//! Nodes are identified by value, and each node other than a root has exactly one parent.  Every
+//! node's depth is worked out up front, which is also where problems such as a node with two
+//! parents or a cycle are found, so queries afterwards can't fail on a malformed tree.
+
+use core::{fmt, hash::Hash};
+use std::collections::{HashMap, HashSet};
+
+/// Why a set of edges doesn't form a tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeError<T> {
+	/// `child` was given both `first` and `second` as its parent.
+	MultipleParents { child: T, first: T, second: T },
+	/// Following parents up from the node leads back to it.
+	Cycle(T),
+}
+
+impl<T: fmt::Display> fmt::Display for TreeError<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::MultipleParents {
+				child,
+				first,
+				second,
+			} => write!(f, "`{child}` has two parents, `{first}` and `{second}`"),
+			Self::Cycle(node) => write!(f, "`{node}` is its own ancestor"),
+		}
+	}
+}
+
+impl<T: fmt::Debug + fmt::Display> std::error::Error for TreeError<T> {}
+
+#[derive(Clone, Debug)]
+pub struct Tree<T> {
+	parents: HashMap<T, T>,
+	depths: HashMap<T, usize>,
+}
+
+impl<T: Clone + Eq + Hash> Tree<T> {
+	/// Builds a tree (or a forest, if there are several roots) from `(parent, child)` pairs.
+	///
+	/// # Errors
+	///
+	/// Fails if any node is given two different parents, or is its own ancestor.
+	pub fn from_edges(edges: impl IntoIterator<Item = (T, T)>) -> Result<Self, TreeError<T>> {
+		let mut parents: HashMap<T, T> = HashMap::new();
+
+		for (parent, child) in edges {
+			if let Some(first) = parents.get(&child) {
+				if *first != parent {
+					return Err(TreeError::MultipleParents {
+						child,
+						first: first.clone(),
+						second: parent,
+					});
+				}
+			}
+
+			parents.insert(child, parent);
+		}
+
+		let mut depths: HashMap<T, usize> = HashMap::new();
+
+		for node in parents.keys().chain(parents.values()) {
+			if depths.contains_key(node) {
+				continue;
+			}
+
+			// Walk up until reaching a root or a node whose depth is already known, then fill in the
+			// depths on the way back down.  The set holds the same nodes as the path, for spotting
+			// cycles without searching it.
+			let mut path = vec![node];
+			let mut on_path = HashSet::from([node]);
+
+			let mut depth = loop {
+				let Some(parent) = parents.get(*path.last().unwrap()) else {
+					break 0;
+				};
+
+				if let Some(depth) = depths.get(parent) {
+					break depth + 1;
+				}
+
+				if !on_path.insert(parent) {
+					return Err(TreeError::Cycle(parent.clone()));
+				}
+
+				path.push(parent);
+			};
+
+			for node in path.into_iter().rev() {
+				depths.insert(node.clone(), depth);
+				depth += 1;
+			}
+		}
+
+		Ok(Self { parents, depths })
+	}
+
+	pub fn contains(&self, node: &T) -> bool {
+		self.depths.contains_key(node)
+	}
+
+	pub fn nodes(&self) -> impl Iterator<Item = &T> {
+		self.depths.keys()
+	}
+
+	pub fn parent(&self, node: &T) -> Option<&T> {
+		self.parents.get(node)
+	}
+
+	/// The node's parent, its parent's parent, and so on up to the root.
+	pub fn ancestors<'t>(&'t self, node: &'t T) -> impl Iterator<Item = &'t T> {
+		core::iter::successors(self.parent(node), |node| self.parent(node))
+	}
+
+	/// The number of edges between the node and its root.
+	pub fn depth(&self, node: &T) -> Option<usize> {
+		self.depths.get(node).copied()
+	}
+
+	/// The sum of every node's depth, which is the number of ancestor-descendant pairs.
+	pub fn total_depth(&self) -> usize {
+		self.depths.values().sum()
+	}
+
+	/// The deepest node which is an ancestor of (or the same as) both `a` and `b`, if they share a
+	/// root.
+	pub fn lowest_common_ancestor<'t>(&'t self, mut a: &'t T, mut b: &'t T) -> Option<&'t T> {
+		let (mut depth_a, mut depth_b) = (self.depth(a)?, self.depth(b)?);
+
+		while depth_a > depth_b {
+			a = self.parent(a)?;
+			depth_a -= 1;
+		}
+
+		while depth_b > depth_a {
+			b = self.parent(b)?;
+			depth_b -= 1;
+		}
+
+		while a != b {
+			a = self.parent(a)?;
+			b = self.parent(b)?;
+		}
+
+		Some(a)
+	}
+
+	/// The number of edges on the path between `a` and `b`, if they share a root.
+	pub fn distance(&self, a: &T, b: &T) -> Option<usize> {
+		let ancestor = self.lowest_common_ancestor(a, b)?;
+		let depth = self.depth(ancestor)?;
+
+		Some(self.depth(a)? - depth + self.depth(b)? - depth)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Tree, TreeError};
+
+	//     1
+	//    / \
+	//   2   3
+	//  / \   \
+	// 4   5   6
+	//         |
+	//         7
+	fn example() -> Tree<u8> {
+		Tree::from_edges([(6, 7), (1, 2), (2, 4), (3, 6), (1, 3), (2, 5)]).unwrap()
+	}
+
+	#[test]
+	fn depths() {
+		let tree = example();
+
+		assert_eq!(tree.depth(&1), Some(0));
+		assert_eq!(tree.depth(&7), Some(3));
+		assert_eq!(tree.depth(&8), None);
+		assert_eq!(tree.total_depth(), 1 + 1 + 2 + 2 + 2 + 3);
+		assert_eq!(
+			tree.ancestors(&7).copied().collect::<Vec<_>>(),
+			vec![6, 3, 1]
+		);
+		assert_eq!(tree.nodes().count(), 7);
+	}
+
+	#[test]
+	fn common_ancestors() {
+		let tree = example();
+
+		assert_eq!(tree.lowest_common_ancestor(&4, &5), Some(&2));
+		assert_eq!(tree.lowest_common_ancestor(&4, &7), Some(&1));
+		assert_eq!(tree.lowest_common_ancestor(&6, &7), Some(&6));
+		assert_eq!(tree.distance(&4, &7), Some(5));
+		assert_eq!(tree.distance(&5, &5), Some(0));
+
+		let forest = Tree::from_edges([(1, 2), (3, 4)]).unwrap();
+		assert_eq!(forest.lowest_common_ancestor(&2, &4), None);
+	}
+
+	#[test]
+	fn malformed() {
+		assert_eq!(
+			Tree::from_edges([(1, 2), (3, 2)]).unwrap_err(),
+			TreeError::MultipleParents {
+				child: 2,
+				first: 1,
+				second: 3
+			}
+		);
+		assert!(matches!(
+			Tree::from_edges([(1, 2), (2, 3), (3, 1), (3, 4)]),
+			Err(TreeError::Cycle(_))
+		));
+		assert!(matches!(
+			Tree::from_edges([(1, 1)]),
+			Err(TreeError::Cycle(1))
+		));
+	}
+}
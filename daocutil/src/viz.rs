@@ -0,0 +1,301 @@
+//! Rendering grids as (optionally colored) text, and playing those renderings back as an
+//! animation in the terminal.
+//!
+//! Animations are opt-in: the runner generated by [`generate_main!`](crate::generate_main)
+//! accepts an `--animate` (or `--animate=FPS`) flag, and [`Animation::from_flag`] only hands out
+//! an animation when that flag was given.  Frames are drawn to stderr so that answers printed to
+//! stdout are unaffected.
+
+use core::fmt;
+use std::{
+	io::{self, Write},
+	sync::OnceLock,
+	thread,
+	time::{Duration, Instant},
+};
+
+pub use nu_ansi_term::{Color, Style};
+
+use crate::automaton::Dense;
+
+/// The frame rate used when `--animate` is given without one.
+pub const DEFAULT_FPS: u32 = 30;
+
+static FPS: OnceLock<u32> = OnceLock::new();
+
+/// A single character cell, along with the style to draw it in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyph {
+	pub symbol: char,
+	pub style: Style,
+}
+
+impl Glyph {
+	pub fn new(symbol: char, style: Style) -> Self {
+		Self { symbol, style }
+	}
+
+	pub fn colored(symbol: char, color: Color) -> Self {
+		Self::new(symbol, color.normal())
+	}
+}
+
+impl From<char> for Glyph {
+	fn from(symbol: char) -> Self {
+		Self::new(symbol, Style::default())
+	}
+}
+
+/// Renders rows of glyphs, one line per row.  Runs of equally-styled glyphs share one set of
+/// escape codes, and unstyled glyphs get none at all.
+pub fn render_rows<Rows, Row>(rows: Rows) -> String
+where
+	Rows: IntoIterator<Item = Row>,
+	Row: IntoIterator<Item = Glyph>,
+{
+	let mut out = String::new();
+
+	for row in rows {
+		let mut run = String::new();
+		let mut run_style = Style::default();
+
+		for glyph in row {
+			if glyph.style != run_style {
+				push_run(&mut out, &run, run_style);
+				run.clear();
+				run_style = glyph.style;
+			}
+
+			run.push(glyph.symbol);
+		}
+
+		push_run(&mut out, &run, run_style);
+		out.push('\n');
+	}
+
+	out
+}
+
+fn push_run(out: &mut String, run: &str, style: Style) {
+	if style.is_plain() {
+		out.push_str(run);
+	} else if !run.is_empty() {
+		out.push_str(&style.paint(run).to_string());
+	}
+}
+
+/// Renders every cell of a dense grid.
+pub fn render_dense<T>(grid: &Dense<T>, glyph: impl Fn(&T) -> Glyph) -> String {
+	let cells: Vec<Glyph> = grid.cells().map(glyph).collect();
+
+	render_rows(cells.chunks(grid.width().max(1)).map(<[Glyph]>::to_vec))
+}
+
+/// Renders glyphs placed at arbitrary `(x, y)` positions, with `y` increasing downwards.  The
+/// frame covers the bounding box of the positions given, and anything not given is drawn as
+/// `background`.  Where positions repeat, later glyphs are drawn over earlier ones.
+pub fn render_sparse(
+	glyphs: impl IntoIterator<Item = ((i32, i32), Glyph)>,
+	background: Glyph,
+) -> String {
+	let glyphs: Vec<((i32, i32), Glyph)> = glyphs.into_iter().collect();
+
+	let Some(((x_min, y_min), (x_max, y_max))) = glyphs.iter().fold(None, |bounds, &((x, y), _)| {
+		Some(match bounds {
+			None => ((x, y), (x, y)),
+			Some(((x_min, y_min), (x_max, y_max))) => {
+				((x.min(x_min), y.min(y_min)), (x.max(x_max), y.max(y_max)))
+			}
+		})
+	}) else {
+		return String::new();
+	};
+
+	let width = usize::try_from(x_max - x_min + 1).unwrap();
+	let height = usize::try_from(y_max - y_min + 1).unwrap();
+	let mut frame = vec![background; width * height];
+
+	for ((x, y), glyph) in glyphs {
+		let (x, y) = (
+			usize::try_from(x - x_min).unwrap(),
+			usize::try_from(y - y_min).unwrap(),
+		);
+		frame[y * width + x] = glyph;
+	}
+
+	render_rows(frame.chunks(width).map(<[Glyph]>::to_vec))
+}
+
+/// A `--animate=FPS` flag whose frame rate isn't a whole number above zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlagError(String);
+
+impl fmt::Display for FlagError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"invalid frame rate `{}` for `--animate`: expected a whole number above zero",
+			self.0
+		)
+	}
+}
+
+impl std::error::Error for FlagError {}
+
+/// Interprets a command-line argument as the animation flag, returning the requested frame rate
+/// if it is one.  Arguments which don't start with `--animate` (or `--animate=`) aren't the flag,
+/// and give `None`.
+pub fn parse_flag(arg: &str) -> Option<Result<u32, FlagError>> {
+	match arg.strip_prefix("--animate")? {
+		"" => Some(Ok(DEFAULT_FPS)),
+		rest => {
+			let fps = rest.strip_prefix('=')?;
+
+			Some(
+				fps
+					.parse()
+					.ok()
+					.filter(|&fps| fps > 0)
+					.ok_or_else(|| FlagError(fps.to_string())),
+			)
+		}
+	}
+}
+
+/// Turns animations on for the rest of the run.  Called by the runner when the flag is given.
+pub fn enable(fps: u32) {
+	let _ = FPS.set(fps);
+}
+
+/// Draws frames in place, replacing the previous frame and pacing them to a fixed frame rate.
+pub struct Animation<W: Write = io::Stderr> {
+	out: W,
+	frame_time: Duration,
+	last_frame: Option<Instant>,
+	lines: usize,
+	failed: bool,
+}
+
+impl Animation {
+	/// An animation drawing to stderr, if animations were enabled on the command line.
+	pub fn from_flag() -> Option<Self> {
+		FPS.get().map(|&fps| Self::new(io::stderr(), fps))
+	}
+}
+
+impl<W: Write> Animation<W> {
+	pub fn new(out: W, fps: u32) -> Self {
+		Self {
+			out,
+			frame_time: Duration::from_secs(1) / fps.max(1),
+			last_frame: None,
+			lines: 0,
+			failed: false,
+		}
+	}
+
+	/// Replaces the previous frame with `frame`, first waiting out the rest of the previous
+	/// frame's time.  If the output can't be written to, the animation quietly stops.
+	pub fn frame(&mut self, frame: &str) {
+		if self.failed {
+			return;
+		}
+
+		if let Some(elapsed) = self.last_frame.map(|last| last.elapsed()) {
+			if let Some(remaining) = self.frame_time.checked_sub(elapsed) {
+				thread::sleep(remaining);
+			}
+		}
+
+		self.failed = self.draw(frame).is_err();
+		self.last_frame = Some(Instant::now());
+	}
+
+	fn draw(&mut self, frame: &str) -> io::Result<()> {
+		if self.lines > 0 {
+			// Move back up to where the previous frame started and clear everything below.
+			write!(self.out, "\x1b[{}A\x1b[J", self.lines)?;
+		}
+
+		self.out.write_all(frame.as_bytes())?;
+
+		if !frame.ends_with('\n') {
+			writeln!(self.out)?;
+		}
+
+		self.lines = frame.lines().count();
+		self.out.flush()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		parse_flag, render_rows, render_sparse, Animation, Color, FlagError, Glyph, DEFAULT_FPS,
+	};
+
+	#[test]
+	fn plain_rows() {
+		let rows = ["ab", "cd"].map(|row| row.chars().map(Glyph::from).collect::<Vec<_>>());
+
+		assert_eq!(render_rows(rows), "ab\ncd\n");
+	}
+
+	#[test]
+	fn styled_runs_share_escapes() {
+		let red = Glyph::colored('#', Color::Red);
+		let rendered = render_rows([vec![red, red, Glyph::from('.')]]);
+
+		assert_eq!(rendered, format!("{}.\n", Color::Red.paint("##")));
+	}
+
+	#[test]
+	fn sparse_bounding_box() {
+		let rendered = render_sparse(
+			[
+				((-1, 0), 'H'.into()),
+				((1, 1), 'T'.into()),
+				((1, 1), 'X'.into()),
+			],
+			'.'.into(),
+		);
+
+		assert_eq!(rendered, "H..\n..X\n");
+		assert_eq!(render_sparse([], '.'.into()), "");
+	}
+
+	#[test]
+	fn flag() {
+		assert_eq!(parse_flag("--animate"), Some(Ok(DEFAULT_FPS)));
+		assert_eq!(parse_flag("--animate=12"), Some(Ok(12)));
+		assert_eq!(
+			parse_flag("--animate=0"),
+			Some(Err(FlagError("0".to_string())))
+		);
+		assert_eq!(
+			parse_flag("--animate=abc")
+				.unwrap()
+				.unwrap_err()
+				.to_string(),
+			"invalid frame rate `abc` for `--animate`: expected a whole number above zero"
+		);
+		assert_eq!(parse_flag("--animated"), None);
+		assert_eq!(parse_flag("day01"), None);
+	}
+
+	#[test]
+	fn frames_redraw_in_place() {
+		let mut out = Vec::new();
+
+		{
+			let mut animation = Animation::new(&mut out, 1_000);
+			animation.frame("ab\ncd\n");
+			animation.frame("ef\ngh");
+		}
+
+		assert_eq!(
+			String::from_utf8(out).unwrap(),
+			"ab\ncd\n\x1b[2A\x1b[Jef\ngh\n"
+		);
+	}
+}